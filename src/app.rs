@@ -73,12 +73,17 @@ impl App {
         let mut progress_interval = 0;
         loop {
             main_activity.update_message();
+            main_activity.player.update_crossfade();
+            main_activity.player.update_ab_loop();
             main_activity.update_queue_items();
+            // player events drive progress and switching to next song
+            main_activity.update_player_msg();
+            main_activity.run();
             if progress_interval == 0 {
-                main_activity.update_progress();
-                main_activity.run();
                 main_activity.update_download_progress();
                 main_activity.update_youtube_search();
+                main_activity.update_replaygain_scan();
+                main_activity.update_sleep_timer();
                 // if let Ok(m) = main_activity.player.dbus_mpris.next() {
                 //     mpris_handler(m, &mut main_activity);
                 // }
@@ -22,25 +22,115 @@ use anyhow::{anyhow, Result};
  * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
  * SOFTWARE.
  */
+use crate::player::Backend;
+use crate::song::replaygain::ReplayGainMode;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs::{self, read_to_string};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub const MUSIC_DIR: &str = "~/Music";
 
 #[derive(Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Termusic {
     pub music_dir: String,
+    // crossfade between songs in seconds, 0 to disable, max 12
+    pub crossfade_duration: u64,
+    // replaygain mode: off, track or album
+    pub replaygain: ReplayGainMode,
+    // extra gain in dB added to songs with replaygain tags
+    pub replaygain_preamp: f64,
+    // gain in dB for songs without replaygain tags
+    pub replaygain_fallback: f64,
+    // name of the equalizer preset in use
+    pub equalizer: String,
+    // load the last played song paused at the saved position on startup
+    pub resume_playback: bool,
+    // quit termusic when sleep timer fires
+    pub sleep_timer_quit: bool,
+    // name of the audio output device, empty for the default one
+    pub output_device: String,
+    // playback backend, gstreamer or rusty. It falls back to the default one if the backend
+    // is not enabled at compile time.
+    pub player_backend: Backend,
+    // embedded cover images larger than this are scaled down and saved as jpeg, in pixels.
+    // 0 keeps the image file as it is.
+    pub cover_max_size: u32,
+    // jpeg quality of the scaled cover images, 1 to 100
+    pub cover_quality: u8,
+    // equalizer presets, 10 bands in dB from 29Hz to 15kHz, range from -24 to 12.
+    // tables must be placed after plain values in toml, so keep this at the end.
+    pub equalizer_presets: Vec<EqualizerPreset>,
+    // default playback rate of files or directories, the closest one is used
+    pub playback_rates: BTreeMap<String, f64>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct EqualizerPreset {
+    pub name: String,
+    pub bands: Vec<f64>,
+}
+
+impl EqualizerPreset {
+    fn new(name: &str, bands: &[f64]) -> Self {
+        Self {
+            name: name.to_string(),
+            bands: bands.to_vec(),
+        }
+    }
 }
 impl Default for Termusic {
     fn default() -> Self {
         Self {
             music_dir: MUSIC_DIR.to_string(),
+            crossfade_duration: 0,
+            replaygain: ReplayGainMode::Off,
+            replaygain_preamp: 0.0,
+            replaygain_fallback: 0.0,
+            equalizer: "flat".to_string(),
+            resume_playback: true,
+            sleep_timer_quit: false,
+            output_device: String::new(),
+            player_backend: Backend::default(),
+            cover_max_size: 0,
+            cover_quality: 85,
+            equalizer_presets: vec![
+                EqualizerPreset::new("flat", &[0.0; 10]),
+                EqualizerPreset::new(
+                    "bass boost",
+                    &[6.0, 5.0, 4.0, 2.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+                ),
+                EqualizerPreset::new(
+                    "vocal",
+                    &[-2.0, -1.0, 0.0, 2.0, 4.0, 4.0, 3.0, 1.0, 0.0, -1.0],
+                ),
+                EqualizerPreset::new("custom", &[0.0; 10]),
+            ],
+            playback_rates: BTreeMap::new(),
         }
     }
 }
 
 impl Termusic {
+    pub fn equalizer_preset(&self) -> Option<&EqualizerPreset> {
+        self.equalizer_presets
+            .iter()
+            .find(|p| p.name == self.equalizer)
+    }
+
+    // playback_rate finds the default rate saved for file or its closest parent directory
+    pub fn playback_rate(&self, file: &str) -> Option<f64> {
+        Path::new(file)
+            .ancestors()
+            .find_map(|p| self.playback_rates.get(p.to_string_lossy().as_ref()))
+            .copied()
+    }
+
+    pub fn set_playback_rate(&mut self, path: &str, rate: f64) {
+        self.playback_rates.insert(path.to_string(), rate);
+    }
+
     pub fn save(&self) -> Result<()> {
         let mut path = get_app_config_path()?;
         path.push("config.toml");
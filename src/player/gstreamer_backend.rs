@@ -0,0 +1,505 @@
+// use crate::dbus::{Loop, Metadata, Mpris, OrgMprisMediaPlayer2Player, Playback};
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use super::{is_stream, PlayerMsg, PlayerState, PlayerTrait, EQUALIZER_BANDS};
+use anyhow::{anyhow, Result};
+use gst::glib::{MainContext, SignalHandlerId};
+use gst::ClockTime;
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_pbutils as gst_pbutils;
+use gstreamer_player as gst_player;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// uri turns a local path or stream url into the uri for playbin
+fn uri(file: &str) -> String {
+    if is_stream(file) {
+        file.to_string()
+    } else {
+        format!("file:///{}", file)
+    }
+}
+
+// Fade holds the outgoing player while crossfading to the next song
+struct Fade {
+    player: gst_player::Player,
+    start: Instant,
+    duration: u64,
+    volume: f64,
+}
+
+pub struct GStreamer {
+    player: gst_player::Player,
+    paused: bool,
+    // linear volume with replaygain applied
+    volume: f64,
+    next_uri: Arc<Mutex<Option<String>>>,
+    gapless_pending: Arc<AtomicBool>,
+    message_tx: Sender<PlayerMsg>,
+    message_rx: Receiver<PlayerMsg>,
+    about_to_finish_id: Option<SignalHandlerId>,
+    // player signals are dispatched in this context, instead of the default main loop
+    context: MainContext,
+    signal_ids: Vec<SignalHandlerId>,
+    bus_ids: Vec<SignalHandlerId>,
+    fade: Option<Fade>,
+    // audio sink in use, None for the default one
+    output_device: Option<gst::Device>,
+    equalizer: [f64; EQUALIZER_BANDS],
+    rate: f64,
+}
+
+impl GStreamer {
+    pub fn new() -> Self {
+        gst::init().expect("Couldn't initialize Gstreamer");
+        let context = MainContext::new();
+        let player = Self::new_player(&context);
+        player.set_volume(0.5);
+
+        let next_uri: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
+        let gapless_pending = Arc::new(AtomicBool::new(false));
+        let (message_tx, message_rx) = mpsc::channel();
+        let about_to_finish_id = Self::connect_gapless(&player, &next_uri, &gapless_pending);
+        let signal_ids = Self::connect_player_signals(&player, &message_tx);
+        let bus_ids = Self::connect_bus_messages(&player, &gapless_pending, &message_tx);
+
+        Self {
+            player,
+            paused: false,
+            volume: 0.5,
+            next_uri,
+            gapless_pending,
+            message_tx,
+            message_rx,
+            about_to_finish_id,
+            context,
+            signal_ids,
+            bus_ids,
+            fade: None,
+            output_device: None,
+            equalizer: [0.0; EQUALIZER_BANDS],
+            rate: 1.0,
+        }
+    }
+
+    fn new_player(context: &MainContext) -> gst_player::Player {
+        let dispatcher = gst_player::PlayerGMainContextSignalDispatcher::new(Some(context));
+        let player = gst_player::Player::new(
+            None,
+            Some(&dispatcher.upcast::<gst_player::PlayerSignalDispatcher>()),
+        );
+        // scaletempo keeps the pitch when rate is changed, and the equalizer stays flat until
+        // set_equalizer. Any missing plugin is skipped.
+        let filter = gst::parse_bin_from_description(
+            "scaletempo ! audioconvert ! equalizer-10bands name=equalizer",
+            true,
+        )
+        .or_else(|_| gst::parse_bin_from_description("equalizer-10bands name=equalizer", true));
+        if let Ok(filter) = filter {
+            player.pipeline().set_property("audio-filter", &filter).ok();
+        }
+        // seek to exact position instead of the nearest key unit, needed by A-B loop
+        let mut config = player.config();
+        config.set_seek_accurate(true);
+        player.set_config(config).ok();
+        player
+    }
+
+    // audio_sinks finds output devices like pulseaudio, pipewire or alsa sinks
+    fn audio_sinks() -> Vec<gst::Device> {
+        let monitor = gst::DeviceMonitor::new();
+        monitor.add_filter(Some("Audio/Sink"), None);
+        if monitor.start().is_err() {
+            return Vec::new();
+        }
+        let devices = monitor.devices();
+        monitor.stop();
+        devices
+    }
+
+    fn apply_output_device(player: &gst_player::Player, device: Option<&gst::Device>) {
+        let sink = device.and_then(|d| d.create_element(None).ok());
+        player.pipeline().set_property("audio-sink", sink).ok();
+    }
+
+    fn apply_equalizer(player: &gst_player::Player, bands: &[f64; EQUALIZER_BANDS]) {
+        let equalizer = player
+            .pipeline()
+            .property("audio-filter")
+            .ok()
+            .and_then(|v| v.get::<Option<gst::Element>>().ok())
+            .flatten()
+            .and_then(|filter| filter.downcast::<gst::Bin>().ok())
+            .and_then(|bin| bin.by_name("equalizer"));
+        if let Some(equalizer) = equalizer {
+            for (i, gain) in bands.iter().enumerate() {
+                equalizer
+                    .set_property(format!("band{}", i).as_str(), gain)
+                    .ok();
+            }
+        }
+    }
+
+    // connect_gapless hooks the playbin of player to the shared next uri, and returns the
+    // handler id so that it could be disconnected when the player is faded out.
+    fn connect_gapless(
+        player: &gst_player::Player,
+        next_uri: &Arc<Mutex<Option<String>>>,
+        gapless_pending: &Arc<AtomicBool>,
+    ) -> Option<SignalHandlerId> {
+        // playbin asks for the next uri shortly before the current one runs out, setting it
+        // here makes the next song start without a gap.
+        let next_uri = next_uri.clone();
+        let gapless_pending = gapless_pending.clone();
+        player
+            .pipeline()
+            .connect("about-to-finish", false, move |args| {
+                if let Ok(playbin) = args[0].get::<gst::Element>() {
+                    if let Ok(mut next_uri) = next_uri.lock() {
+                        if let Some(uri) = next_uri.take() {
+                            if playbin.set_property("uri", &uri).is_ok() {
+                                gapless_pending.store(true, Ordering::SeqCst);
+                            }
+                        }
+                    }
+                }
+                None
+            })
+            .ok()
+    }
+
+    // connect_bus_messages watches the bus of the playbin for gapless switches and titles of
+    // network streams, and returns the handler ids to disconnect when the player is faded out.
+    fn connect_bus_messages(
+        player: &gst_player::Player,
+        gapless_pending: &Arc<AtomicBool>,
+        message_tx: &Sender<PlayerMsg>,
+    ) -> Vec<SignalHandlerId> {
+        let playbin = player.pipeline();
+        let Some(bus) = playbin.bus() else {
+            return Vec::new();
+        };
+
+        // stream-start is posted when the preloaded uri actually begins to play
+        let gapless_pending = gapless_pending.clone();
+        let tx = message_tx.clone();
+        let stream_start_id = bus.connect_message(Some("stream-start"), move |_, _| {
+            if gapless_pending.swap(false, Ordering::SeqCst) {
+                tx.send(PlayerMsg::CurrentTrackUpdated).ok();
+            }
+        });
+
+        // souphttpsrc turns ICY StreamTitle into title tags. The bus belongs to playbin, so
+        // only a weak reference is kept here, or the pipeline would never be freed.
+        let playbin = playbin.downgrade();
+        let tx = message_tx.clone();
+        let tag_id = bus.connect_message(Some("tag"), move |_, msg| {
+            let streaming = playbin
+                .upgrade()
+                .and_then(|playbin| playbin.property("current-uri").ok())
+                .and_then(|v| v.get::<Option<String>>().ok().flatten())
+                .is_some_and(|uri| is_stream(&uri));
+            if let (true, gst::MessageView::Tag(t)) = (streaming, msg.view()) {
+                if let Some(title) = t.tags().get::<gst::tags::Title>() {
+                    tx.send(PlayerMsg::StreamTitle(title.get().to_string()))
+                        .ok();
+                }
+            }
+        });
+        vec![stream_start_id, tag_id]
+    }
+
+    // connect_player_signals sends events of player to the main activity. The signals are
+    // emitted in context, when next_msg is called.
+    fn connect_player_signals(
+        player: &gst_player::Player,
+        message_tx: &Sender<PlayerMsg>,
+    ) -> Vec<SignalHandlerId> {
+        let tx = message_tx.clone();
+        let eos_id = player.connect_end_of_stream(move |_| {
+            tx.send(PlayerMsg::EndOfStream).ok();
+        });
+        let tx = message_tx.clone();
+        let position_id = player.connect_position_updated(move |_, _| {
+            tx.send(PlayerMsg::PositionUpdated).ok();
+        });
+        let tx = message_tx.clone();
+        let duration_id = player.connect_duration_changed(move |_, _| {
+            tx.send(PlayerMsg::DurationChanged).ok();
+        });
+        let tx = message_tx.clone();
+        let state_id = player.connect_state_changed(move |_, state| {
+            let state = match state {
+                gst_player::PlayerState::Buffering => PlayerState::Buffering,
+                gst_player::PlayerState::Paused => PlayerState::Paused,
+                gst_player::PlayerState::Playing => PlayerState::Playing,
+                _ => PlayerState::Stopped,
+            };
+            tx.send(PlayerMsg::StateChanged(state)).ok();
+        });
+        let tx = message_tx.clone();
+        let error_id = player.connect_error(move |player, err| {
+            // current-uri of playbin is the right one after gapless switch
+            let file = player
+                .pipeline()
+                .property("current-uri")
+                .ok()
+                .and_then(|v| v.get::<Option<String>>().ok().flatten())
+                .map(|uri| uri.trim_start_matches("file:///").to_string());
+            tx.send(PlayerMsg::Error(file, err.to_string())).ok();
+        });
+        let tx = message_tx.clone();
+        let warning_id = player.connect_warning(move |_, warning| {
+            tx.send(PlayerMsg::Warning(warning.to_string())).ok();
+        });
+        vec![
+            eos_id,
+            position_id,
+            duration_id,
+            state_id,
+            error_id,
+            warning_id,
+        ]
+    }
+
+    pub fn duration(song: &str) -> Duration {
+        gst::init().ok();
+        let timeout: ClockTime = ClockTime::from_seconds(1);
+        let mut duration = ClockTime::from_seconds(0);
+        if let Ok(discoverer) = gst_pbutils::Discoverer::new(timeout) {
+            if let Ok(info) = discoverer.discover_uri(&format!("file:///{}", song)) {
+                if let Some(d) = info.duration() {
+                    duration = d;
+                }
+            }
+        }
+        duration.into()
+    }
+
+    fn set_uri(&mut self, song_str: &str) {
+        self.finish_fade();
+        self.gapless_pending.store(false, Ordering::SeqCst);
+        self.player.set_uri(&uri(song_str));
+    }
+
+    // finish_fade stops the outgoing player at once, used before pause, seek and skip
+    fn finish_fade(&mut self) {
+        if let Some(fade) = self.fade.take() {
+            fade.player.stop();
+            self.player.set_volume(self.volume);
+        }
+    }
+}
+
+impl PlayerTrait for GStreamer {
+    fn queue_and_play(&mut self, song_str: &str) {
+        self.set_uri(song_str);
+        self.paused = false;
+        self.player.play();
+    }
+
+    fn queue_and_pause(&mut self, song_str: &str) {
+        self.set_uri(song_str);
+        self.paused = true;
+        self.player.pause();
+    }
+
+    fn pause(&mut self) {
+        self.finish_fade();
+        self.paused = true;
+        self.player.pause();
+    }
+
+    fn resume(&mut self) {
+        self.paused = false;
+        self.player.play();
+    }
+
+    fn seek_to_ms(&mut self, ms: u64) {
+        self.finish_fade();
+        self.player.seek(ClockTime::from_mseconds(ms));
+    }
+
+    fn position_ms(&self) -> u64 {
+        self.player.position().map_or(0, ClockTime::mseconds)
+    }
+
+    fn duration_ms(&self) -> Option<u64> {
+        self.player.duration().map(ClockTime::mseconds)
+    }
+
+    fn set_volume(&mut self, volume: f64) {
+        self.volume = volume;
+        // during crossfade the volume is applied by update_crossfade
+        if !self.is_fading() {
+            self.player.set_volume(self.volume);
+        }
+    }
+
+    // next_msg emits pending player signals first, so it should be called on every tick
+    fn next_msg(&mut self) -> Option<PlayerMsg> {
+        while self.context.iteration(false) {}
+        self.message_rx.try_recv().ok()
+    }
+
+    // crossfade_and_play starts the song in a new player with volume 0, and fades it in while
+    // the current player fades out.
+    fn crossfade_and_play(&mut self, song_str: &str, secs: u64) {
+        if secs == 0 || self.paused || self.player.uri().is_none() {
+            self.queue_and_play(song_str);
+            return;
+        }
+        self.finish_fade();
+        self.gapless_pending.store(false, Ordering::SeqCst);
+
+        let player = Self::new_player(&self.context);
+        player.set_volume(0.0);
+        Self::apply_equalizer(&player, &self.equalizer);
+        Self::apply_output_device(&player, self.output_device.as_ref());
+        player.set_rate(self.rate);
+        let about_to_finish_id =
+            Self::connect_gapless(&player, &self.next_uri, &self.gapless_pending);
+        let signal_ids = Self::connect_player_signals(&player, &self.message_tx);
+        let bus_ids = Self::connect_bus_messages(&player, &self.gapless_pending, &self.message_tx);
+        let old_player = std::mem::replace(&mut self.player, player);
+        // the outgoing player should not pick up the next song or report its end anymore
+        if let Some(id) = std::mem::replace(&mut self.about_to_finish_id, about_to_finish_id) {
+            old_player.pipeline().disconnect(id);
+        }
+        for id in std::mem::replace(&mut self.signal_ids, signal_ids) {
+            old_player.disconnect(id);
+        }
+        let old_bus_ids = std::mem::replace(&mut self.bus_ids, bus_ids);
+        if let Some(bus) = old_player.pipeline().bus() {
+            for id in old_bus_ids {
+                bus.disconnect(id);
+            }
+        }
+        self.fade = Some(Fade {
+            player: old_player,
+            start: Instant::now(),
+            duration: secs,
+            volume: self.volume,
+        });
+
+        self.player.set_uri(&uri(song_str));
+        self.paused = false;
+        self.player.play();
+    }
+
+    // update_crossfade adjusts the volume of both players
+    #[allow(clippy::cast_precision_loss)]
+    fn update_crossfade(&mut self) {
+        let progress = match &self.fade {
+            Some(fade) => fade.start.elapsed().as_secs_f64() / fade.duration as f64,
+            None => return,
+        };
+        if progress >= 1.0 {
+            self.finish_fade();
+            return;
+        }
+        if let Some(fade) = &self.fade {
+            fade.player.set_volume(fade.volume * (1.0 - progress));
+        }
+        self.player.set_volume(self.volume * progress);
+    }
+
+    fn finish_crossfade(&mut self) {
+        self.finish_fade();
+    }
+
+    fn is_fading(&self) -> bool {
+        self.fade.is_some()
+    }
+
+    // playbin switches to the next uri without a gap
+    fn enqueue_next(&mut self, song_str: Option<&str>) {
+        if let Ok(mut next_uri) = self.next_uri.lock() {
+            *next_uri = song_str.map(uri);
+        }
+    }
+
+    fn has_next(&self) -> bool {
+        if self.gapless_pending.load(Ordering::SeqCst) {
+            return true;
+        }
+        self.next_uri.lock().is_ok_and(|n| n.is_some())
+    }
+
+    // pitch is kept by scaletempo
+    fn set_rate(&mut self, rate: f64) {
+        self.rate = rate;
+        self.player.set_rate(self.rate);
+        if let Some(fade) = &self.fade {
+            fade.player.set_rate(self.rate);
+        }
+    }
+
+    fn set_equalizer(&mut self, bands: &[f64; EQUALIZER_BANDS]) {
+        self.equalizer = *bands;
+        Self::apply_equalizer(&self.player, &self.equalizer);
+        if let Some(fade) = &self.fade {
+            Self::apply_equalizer(&fade.player, &self.equalizer);
+        }
+    }
+
+    fn output_devices(&self) -> Vec<String> {
+        Self::audio_sinks()
+            .iter()
+            .map(|d| d.display_name().to_string())
+            .collect()
+    }
+
+    fn set_output_device(&mut self, name: Option<&str>) -> Result<()> {
+        let device = match name {
+            Some(name) => Some(
+                Self::audio_sinks()
+                    .into_iter()
+                    .find(|d| d.display_name() == name)
+                    .ok_or_else(|| anyhow!("output device {} not found", name))?,
+            ),
+            None => None,
+        };
+        self.finish_fade();
+        self.output_device = device;
+
+        // audio-sink can only be changed when pipeline is stopped
+        let position = self.position_ms();
+        self.player.stop();
+        Self::apply_output_device(&self.player, self.output_device.as_ref());
+        if self.player.uri().is_some() {
+            if self.paused {
+                self.player.pause();
+            } else {
+                self.player.play();
+            }
+            self.player.set_rate(self.rate);
+            self.seek_to_ms(position);
+        }
+        Ok(())
+    }
+}
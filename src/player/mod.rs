@@ -0,0 +1,717 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+#[cfg(feature = "gst")]
+mod gstreamer_backend;
+mod null_backend;
+#[cfg(feature = "rusty")]
+mod rusty_backend;
+
+#[cfg(feature = "gst")]
+use gstreamer_backend::GStreamer;
+pub use null_backend::NullPlayer;
+#[cfg(feature = "rusty")]
+use rusty_backend::RustyBackend;
+
+use crate::song::replaygain::{ReplayGain, ReplayGainMode};
+#[cfg(feature = "mpris")]
+use crate::song::Song;
+#[cfg(feature = "mpris")]
+use crate::souvlaki::{
+    LoopStatus, MediaControlEvent, MediaControls, MediaMetadata, MediaPlayback, PlatformConfig,
+};
+use anyhow::{bail, Result};
+use log::error;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "mpris")]
+use std::str::FromStr;
+#[cfg(feature = "mpris")]
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+pub const MAX_CROSSFADE_DURATION: u64 = 12;
+// volume is linear and allows up to 10.0 with replaygain
+const MAX_VOLUME: f64 = 10.0;
+// 10 bands, centered at 29, 59, 119, 237, 474, 947, 1889, 3770, 7523 and 15011 Hz
+pub const EQUALIZER_BANDS: usize = 10;
+const EQUALIZER_MIN_GAIN: f64 = -24.0;
+const EQUALIZER_MAX_GAIN: f64 = 12.0;
+pub const MIN_RATE: f64 = 0.5;
+pub const MAX_RATE: f64 = 3.0;
+// seconds to fade out before the sleep timer pauses playback
+pub const FADE_OUT_DURATION: u64 = 5;
+
+// is_stream tells whether file is a network stream instead of a local file
+pub fn is_stream(file: &str) -> bool {
+    file.starts_with("http://") || file.starts_with("https://")
+}
+
+// PlayerMsg is sent from backends to the main activity
+#[cfg_attr(not(feature = "gst"), allow(dead_code))]
+pub enum PlayerMsg {
+    // the preloaded next song started playing without a gap
+    CurrentTrackUpdated,
+    // file that failed to play, if known, and the error
+    Error(Option<String>, String),
+    Warning(String),
+    EndOfStream,
+    PositionUpdated,
+    DurationChanged,
+    StateChanged(PlayerState),
+    // title of the stream from ICY metadata, like StreamTitle='Artist - Title'
+    StreamTitle(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(not(feature = "gst"), allow(dead_code))]
+pub enum PlayerState {
+    Stopped,
+    Buffering,
+    Paused,
+    Playing,
+}
+
+// Backend is chosen by player_backend in config, the ones not enabled by cargo features fall
+// back to the default one
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    Gstreamer,
+    Rusty,
+}
+
+impl Default for Backend {
+    #[cfg(feature = "gst")]
+    fn default() -> Self {
+        Self::Gstreamer
+    }
+
+    #[cfg(not(feature = "gst"))]
+    fn default() -> Self {
+        Self::Rusty
+    }
+}
+
+// PlayerTrait is implemented by playback backends. Volume, replaygain, fading out and A-B loop
+// are handled by GeneralPlayer on top of it, the optional features default to nothing.
+pub trait PlayerTrait {
+    fn queue_and_play(&mut self, file: &str);
+    // queue_and_pause loads the song paused at the beginning
+    fn queue_and_pause(&mut self, file: &str);
+    fn pause(&mut self);
+    fn resume(&mut self);
+    fn seek_to_ms(&mut self, ms: u64);
+    fn position_ms(&self) -> u64;
+    fn duration_ms(&self) -> Option<u64>;
+    // set_volume takes linear volume with replaygain applied, up to MAX_VOLUME
+    fn set_volume(&mut self, volume: f64);
+    // next_msg returns the pending events, like end of stream or position change
+    fn next_msg(&mut self) -> Option<PlayerMsg>;
+
+    // crossfade_and_play fades the current song out in secs while the new one fades in
+    fn crossfade_and_play(&mut self, file: &str, _secs: u64) {
+        self.queue_and_play(file);
+    }
+    // update_crossfade is called on every tick
+    fn update_crossfade(&mut self) {}
+    // finish_crossfade stops the outgoing song at once
+    fn finish_crossfade(&mut self) {}
+    fn is_fading(&self) -> bool {
+        false
+    }
+    // enqueue_next sets the song to be played right after the current one without a gap.
+    // None clears it.
+    fn enqueue_next(&mut self, _file: Option<&str>) {}
+    // has_next tells whether the end of current song will be handled by gapless playback
+    fn has_next(&self) -> bool {
+        false
+    }
+    fn set_rate(&mut self, _rate: f64) {}
+    fn set_equalizer(&mut self, _bands: &[f64; EQUALIZER_BANDS]) {}
+    fn output_devices(&self) -> Vec<String> {
+        Vec::new()
+    }
+    // set_output_device switches to the device with the name, or the default one if name is
+    // None. Current song goes on at the same position.
+    fn set_output_device(&mut self, _name: Option<&str>) -> Result<()> {
+        bail!("output device is not supported by this backend")
+    }
+}
+
+fn try_new_backend(backend: Backend) -> Option<Box<dyn PlayerTrait>> {
+    match backend {
+        #[cfg(feature = "gst")]
+        Backend::Gstreamer => Some(Box::new(GStreamer::new())),
+        #[cfg(feature = "rusty")]
+        Backend::Rusty => Some(Box::new(RustyBackend::new())),
+        #[allow(unreachable_patterns)]
+        _ => None,
+    }
+}
+
+pub fn new_backend(backend: Backend) -> Box<dyn PlayerTrait> {
+    try_new_backend(backend)
+        .or_else(|| {
+            error!(
+                "{:?} backend is not enabled, using the default one",
+                backend
+            );
+            try_new_backend(Backend::default())
+        })
+        .unwrap_or_else(|| {
+            error!("no playback backend is enabled");
+            Box::new(NullPlayer::default())
+        })
+}
+
+// duration reads the length of file, zero if it's unknown. It doesn't follow player_backend,
+// GStreamer is preferred whenever it's compiled in, as rodio often has no total duration for
+// mp3 and ogg. rodio is only asked when GStreamer fails or isn't there.
+#[cfg_attr(not(any(feature = "gst", feature = "rusty")), allow(unused_variables))]
+pub fn duration(file: &str) -> Duration {
+    let duration = Duration::from_secs(0);
+    #[cfg(feature = "gst")]
+    let duration = GStreamer::duration(file);
+    #[cfg(feature = "rusty")]
+    let duration = if duration.is_zero() {
+        RustyBackend::duration(file)
+    } else {
+        duration
+    };
+    duration
+}
+
+// GeneralPlayer is what the activity talks to, whichever backend is in use
+pub struct GeneralPlayer {
+    backend: Box<dyn PlayerTrait>,
+    paused: bool,
+    volume: f64,
+    crossfade_duration: u64,
+    // start of fading out before pause
+    fade_out: Option<Instant>,
+    // A-B loop points in milliseconds
+    loop_a: Option<u64>,
+    loop_b: Option<u64>,
+    replaygain_mode: ReplayGainMode,
+    replaygain_preamp: f64,
+    replaygain_fallback: f64,
+    // linear factor applied on top of volume for the current song
+    gain: f64,
+    // gain of each equalizer band in dB
+    equalizer: [f64; EQUALIZER_BANDS],
+    rate: f64,
+    #[cfg(feature = "mpris")]
+    controls: MediaControls,
+    #[cfg(feature = "mpris")]
+    pub rx: Receiver<MediaControlEvent>,
+}
+
+impl GeneralPlayer {
+    pub fn new(backend: Box<dyn PlayerTrait>) -> Self {
+        #[cfg(feature = "mpris")]
+        let config = PlatformConfig {
+            dbus_name: "termusic",
+            display_name: "Termuisc in Rust",
+        };
+
+        #[cfg(feature = "mpris")]
+        let mut controls = MediaControls::new(config);
+
+        #[cfg(feature = "mpris")]
+        let (tx, rx) = mpsc::sync_channel(32);
+        // The closure must be Send and have a static lifetime.
+        #[cfg(feature = "mpris")]
+        controls
+            .attach(move |event: MediaControlEvent| {
+                tx.send(event).ok();
+            })
+            .unwrap();
+
+        let mut player = Self {
+            backend,
+            paused: false,
+            volume: 0.5,
+            crossfade_duration: 0,
+            fade_out: None,
+            loop_a: None,
+            loop_b: None,
+            replaygain_mode: ReplayGainMode::Off,
+            replaygain_preamp: 0.0,
+            replaygain_fallback: 0.0,
+            gain: 1.0,
+            equalizer: [0.0; EQUALIZER_BANDS],
+            rate: 1.0,
+            #[cfg(feature = "mpris")]
+            controls,
+            #[cfg(feature = "mpris")]
+            rx,
+        };
+        player.backend.set_volume(player.effective_volume());
+        player
+    }
+
+    // set_backend replaces the backend, settings like volume and equalizer are kept
+    pub fn set_backend(&mut self, backend: Box<dyn PlayerTrait>) {
+        self.backend = backend;
+        self.paused = false;
+        self.backend.set_volume(self.effective_volume());
+        self.backend.set_equalizer(&self.equalizer);
+        self.backend.set_rate(self.rate);
+    }
+
+    pub fn queue_and_play(&mut self, song_str: &str) {
+        self.clear_ab_loop();
+        self.paused = false;
+        self.backend.queue_and_play(song_str);
+        self.update_metadata(song_str);
+    }
+
+    pub fn queue_and_pause(&mut self, song_str: &str) {
+        self.clear_ab_loop();
+        self.paused = true;
+        self.backend.queue_and_pause(song_str);
+
+        #[cfg(feature = "mpris")]
+        self.controls
+            .set_playback(MediaPlayback::Paused { progress: None })
+            .ok();
+    }
+
+    // crossfade_and_play falls back to queue_and_play if crossfade is off or nothing is
+    // playing.
+    pub fn crossfade_and_play(&mut self, song_str: &str) {
+        if self.crossfade_duration == 0 || self.paused {
+            self.queue_and_play(song_str);
+            return;
+        }
+        self.clear_ab_loop();
+        self.backend
+            .crossfade_and_play(song_str, self.crossfade_duration);
+        self.update_metadata(song_str);
+    }
+
+    // update_crossfade adjusts the volume of both songs, should be called on every tick
+    pub fn update_crossfade(&mut self) {
+        self.backend.update_crossfade();
+    }
+
+    pub fn is_fading(&self) -> bool {
+        self.backend.is_fading()
+    }
+
+    pub fn set_crossfade(&mut self, secs: u64) {
+        self.crossfade_duration = secs.min(MAX_CROSSFADE_DURATION);
+        if self.crossfade_duration == 0 {
+            self.backend.finish_crossfade();
+        }
+    }
+
+    pub const fn crossfade_duration(&self) -> u64 {
+        self.crossfade_duration
+    }
+
+    // start_fade_out lowers the volume to 0 in FADE_OUT_DURATION, then update_fade_out pauses
+    pub fn start_fade_out(&mut self) {
+        self.backend.finish_crossfade();
+        if self.fade_out.is_none() {
+            self.fade_out = Some(Instant::now());
+        }
+    }
+
+    // update_fade_out should be called on ticks, it returns true when fading out is finished
+    // and playback is paused
+    #[allow(clippy::cast_precision_loss)]
+    pub fn update_fade_out(&mut self) -> bool {
+        let progress = match self.fade_out {
+            Some(start) => start.elapsed().as_secs_f64() / FADE_OUT_DURATION as f64,
+            None => return false,
+        };
+        if progress >= 1.0 {
+            self.pause();
+            return true;
+        }
+        self.backend
+            .set_volume(self.effective_volume() * (1.0 - progress));
+        false
+    }
+
+    pub fn cancel_fade_out(&mut self) {
+        if self.fade_out.take().is_some() {
+            self.backend.set_volume(self.effective_volume());
+        }
+    }
+
+    pub const fn set_replaygain_config(
+        &mut self,
+        mode: ReplayGainMode,
+        preamp: f64,
+        fallback: f64,
+    ) {
+        self.replaygain_mode = mode;
+        self.replaygain_preamp = preamp;
+        self.replaygain_fallback = fallback;
+    }
+
+    // apply_replaygain adjusts the volume for the song just started
+    pub fn apply_replaygain(&mut self, replaygain: &ReplayGain) {
+        self.gain = replaygain.volume_factor(
+            self.replaygain_mode,
+            self.replaygain_preamp,
+            self.replaygain_fallback,
+        );
+        self.backend.set_volume(self.effective_volume());
+    }
+
+    // set_equalizer takes gains in dB from low to high frequency, missing bands are flat
+    pub fn set_equalizer(&mut self, bands: &[f64]) {
+        let mut equalizer = [0.0; EQUALIZER_BANDS];
+        for (band, gain) in equalizer.iter_mut().zip(bands) {
+            *band = gain.clamp(EQUALIZER_MIN_GAIN, EQUALIZER_MAX_GAIN);
+        }
+        self.equalizer = equalizer;
+        self.backend.set_equalizer(&self.equalizer);
+    }
+
+    // set_rate changes playback speed, rate is rounded to 0.1.
+    pub fn set_rate(&mut self, rate: f64) {
+        self.rate = ((rate * 10.0).round() / 10.0).clamp(MIN_RATE, MAX_RATE);
+        self.backend.set_rate(self.rate);
+    }
+
+    pub const fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    fn effective_volume(&self) -> f64 {
+        (self.volume * self.gain).min(MAX_VOLUME)
+    }
+
+    pub fn output_devices(&self) -> Vec<String> {
+        self.backend.output_devices()
+    }
+
+    pub fn set_output_device(&mut self, name: Option<&str>) -> Result<()> {
+        self.backend.set_output_device(name)
+    }
+
+    // update_metadata refreshes the song info shown by mpris, it's also called after a gapless
+    // switch as the player doesn't go through queue_and_play then.
+    #[cfg_attr(
+        not(feature = "mpris"),
+        allow(
+            unused_variables,
+            clippy::unused_self,
+            clippy::missing_const_for_fn,
+            clippy::needless_pass_by_ref_mut
+        )
+    )]
+    pub fn update_metadata(&mut self, song_str: &str) {
+        #[cfg(feature = "mpris")]
+        if let Ok(song) = Song::from_str(song_str) {
+            self.controls.set_metadata(MediaMetadata {
+                title: Some(song.title().unwrap_or("Unknown Title")),
+                artist: Some(song.artist().unwrap_or("Unknown Artist")),
+                album: Some(song.album().unwrap_or("")),
+                ..MediaMetadata::default()
+            });
+        }
+        #[cfg(feature = "mpris")]
+        self.controls
+            .set_playback(MediaPlayback::Playing { progress: None })
+            .ok();
+    }
+
+    // update_stream_metadata shows the station and title of the stream over mpris
+    #[cfg_attr(
+        not(feature = "mpris"),
+        allow(
+            unused_variables,
+            clippy::unused_self,
+            clippy::missing_const_for_fn,
+            clippy::needless_pass_by_ref_mut
+        )
+    )]
+    pub fn update_stream_metadata(&mut self, station: &str, title: Option<&str>) {
+        #[cfg(feature = "mpris")]
+        self.controls.set_metadata(MediaMetadata {
+            title: Some(title.unwrap_or(station)),
+            artist: Some(station),
+            ..MediaMetadata::default()
+        });
+    }
+
+    #[cfg(feature = "mpris")]
+    pub fn set_loop_status(&mut self, status: LoopStatus) {
+        self.controls.set_loop_status(status);
+    }
+
+    pub fn enqueue_next(&mut self, song_str: Option<&str>) {
+        self.backend.enqueue_next(song_str);
+    }
+
+    pub fn has_next(&self) -> bool {
+        self.backend.has_next()
+    }
+
+    pub fn next_msg(&mut self) -> Option<PlayerMsg> {
+        self.backend.next_msg()
+    }
+
+    pub fn volume_up(&mut self) {
+        self.set_volume(self.volume + 0.05);
+    }
+
+    pub fn volume_down(&mut self) {
+        self.set_volume(self.volume - 0.05);
+    }
+
+    pub const fn volume(&self) -> f64 {
+        self.volume
+    }
+
+    pub fn set_volume(&mut self, volume: f64) {
+        self.volume = volume.clamp(0.0, 1.0);
+        self.backend.set_volume(self.effective_volume());
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+        self.backend.pause();
+        self.cancel_fade_out();
+
+        #[cfg(feature = "mpris")]
+        self.controls
+            .set_playback(MediaPlayback::Paused { progress: None })
+            .ok();
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.backend.resume();
+
+        #[cfg(feature = "mpris")]
+        self.controls
+            .set_playback(MediaPlayback::Playing { progress: None })
+            .ok();
+    }
+
+    pub const fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn seek(&mut self, secs: i64) -> Result<()> {
+        let time_pos = self.position_ms();
+        let duration = self.backend.duration_ms().unwrap_or(0);
+        let offset = secs.unsigned_abs() * 1000;
+        let seek_pos = if secs >= 0 {
+            time_pos + offset
+        } else {
+            time_pos.saturating_sub(offset)
+        };
+
+        if seek_pos > duration {
+            bail! {"exceed max length"};
+        }
+        self.backend.seek_to_ms(seek_pos);
+        Ok(())
+    }
+
+    pub fn seek_to_ms(&mut self, ms: u64) {
+        self.backend.seek_to_ms(ms);
+    }
+
+    pub fn position_ms(&self) -> u64 {
+        self.backend.position_ms()
+    }
+
+    // toggle_loop_a marks A at current position, or clears the loop if A is marked already
+    pub fn toggle_loop_a(&mut self) {
+        if self.loop_a.is_some() {
+            self.clear_ab_loop();
+        } else {
+            self.loop_a = Some(self.position_ms());
+        }
+    }
+
+    // toggle_loop_b marks B at current position, or clears it if B is marked already. B
+    // must come after A.
+    pub fn toggle_loop_b(&mut self) {
+        if self.loop_b.is_some() {
+            self.loop_b = None;
+            return;
+        }
+        let pos = self.position_ms();
+        if self.loop_a.is_some_and(|a| pos > a) {
+            self.loop_b = Some(pos);
+        }
+    }
+
+    pub const fn clear_ab_loop(&mut self) {
+        self.loop_a = None;
+        self.loop_b = None;
+    }
+
+    pub const fn ab_loop(&self) -> (Option<u64>, Option<u64>) {
+        (self.loop_a, self.loop_b)
+    }
+
+    // update_ab_loop seeks back to A once position passes B, should be called on every tick
+    pub fn update_ab_loop(&mut self) {
+        if let (Some(a), Some(b)) = (self.loop_a, self.loop_b) {
+            if !self.paused && self.position_ms() >= b {
+                self.seek_to_ms(a);
+            }
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    pub fn get_progress(&self) -> (f64, u64, u64) {
+        let time_pos = self.position_ms();
+        // duration is unknown for streams, or before the song is loaded
+        let duration = self.backend.duration_ms().unwrap_or(0);
+        if duration == 0 {
+            return (0.0, time_pos / 1000, 0);
+        }
+        // percent is calculated in milliseconds, so the bar moves smoothly
+        let percent = time_pos as f64 / (duration as f64);
+        (percent, time_pos / 1000, duration / 1000)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::player::{
+        GeneralPlayer, NullPlayer, EQUALIZER_BANDS, EQUALIZER_MAX_GAIN, EQUALIZER_MIN_GAIN,
+        FADE_OUT_DURATION, MAX_RATE, MIN_RATE,
+    };
+    use crate::song::replaygain::{ReplayGain, ReplayGainMode};
+    use pretty_assertions::assert_eq;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_ab_loop() {
+        let mut player = GeneralPlayer::new(Box::new(NullPlayer::default()));
+        player.queue_and_play("song.mp3");
+        player.seek_to_ms(1_000);
+        player.toggle_loop_a();
+        // B before A is ignored
+        player.seek_to_ms(500);
+        player.toggle_loop_b();
+        assert_eq!(player.ab_loop(), (Some(1_000), None));
+
+        player.seek_to_ms(3_000);
+        player.toggle_loop_b();
+        assert_eq!(player.ab_loop(), (Some(1_000), Some(3_000)));
+        player.update_ab_loop();
+        assert_eq!(player.position_ms(), 1_000);
+
+        player.queue_and_play("next.mp3");
+        assert_eq!(player.ab_loop(), (None, None));
+    }
+
+    #[test]
+    fn test_fade_out_cancelled_at_end_of_track() {
+        let backend = NullPlayer::default();
+        let mut player = GeneralPlayer::new(Box::new(backend.clone()));
+        player.set_volume(0.8);
+        player.queue_and_play("song.mp3");
+        player.start_fade_out();
+        // half way through fading out
+        player.fade_out =
+            Instant::now().checked_sub(Duration::from_millis(FADE_OUT_DURATION * 500));
+        assert!(!player.update_fade_out());
+        assert!(backend.state.borrow().volume < 0.5);
+
+        // the track ends before fading out is finished
+        player.cancel_fade_out();
+        assert!(player.fade_out.is_none());
+        assert!((backend.state.borrow().volume - 0.8).abs() < f64::EPSILON);
+
+        // a new fade starts from the beginning instead of pausing at once
+        player.resume();
+        player.start_fade_out();
+        assert!(!player.update_fade_out());
+        assert!(!backend.state.borrow().paused);
+    }
+
+    #[test]
+    fn test_volume_with_replaygain() {
+        let backend = NullPlayer::default();
+        let mut player = GeneralPlayer::new(Box::new(backend.clone()));
+        player.set_replaygain_config(ReplayGainMode::Track, 0.0, 0.0);
+        player.set_volume(0.5);
+        // +6.02 dB doubles the volume
+        player.apply_replaygain(&ReplayGain {
+            track_gain: Some(6.02),
+            ..ReplayGain::default()
+        });
+        assert!((backend.state.borrow().volume - 1.0).abs() < 0.001);
+        // volume shown to the user doesn't include the gain
+        assert!((player.volume() - 0.5).abs() < f64::EPSILON);
+
+        // peak keeps it from clipping
+        player.apply_replaygain(&ReplayGain {
+            track_gain: Some(6.02),
+            track_peak: Some(0.8),
+            ..ReplayGain::default()
+        });
+        assert!((backend.state.borrow().volume - 0.625).abs() < 0.001);
+
+        player.set_volume(2.0);
+        assert!((player.volume() - 1.0).abs() < f64::EPSILON);
+        assert!((backend.state.borrow().volume - 1.25).abs() < 0.001);
+
+        player.set_replaygain_config(ReplayGainMode::Off, 0.0, 0.0);
+        player.apply_replaygain(&ReplayGain::default());
+        assert!((backend.state.borrow().volume - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_rate_clamp() {
+        let backend = NullPlayer::default();
+        let mut player = GeneralPlayer::new(Box::new(backend.clone()));
+        player.set_rate(1.24);
+        assert!((player.rate() - 1.2).abs() < f64::EPSILON);
+        assert!((backend.state.borrow().rate - 1.2).abs() < f64::EPSILON);
+        player.set_rate(0.1);
+        assert!((backend.state.borrow().rate - MIN_RATE).abs() < f64::EPSILON);
+        player.set_rate(10.0);
+        assert!((backend.state.borrow().rate - MAX_RATE).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_equalizer_clamp() {
+        let backend = NullPlayer::default();
+        let mut player = GeneralPlayer::new(Box::new(backend.clone()));
+        // missing bands are flat
+        player.set_equalizer(&[-30.0, 20.0, 3.0]);
+        let mut expected = [0.0; EQUALIZER_BANDS];
+        expected[0] = EQUALIZER_MIN_GAIN;
+        expected[1] = EQUALIZER_MAX_GAIN;
+        expected[2] = 3.0;
+        for (band, gain) in backend.state.borrow().equalizer.iter().zip(expected) {
+            assert!((band - gain).abs() < f64::EPSILON);
+        }
+    }
+}
@@ -0,0 +1,95 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use super::{PlayerMsg, PlayerTrait, EQUALIZER_BANDS};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// NullPlayer plays nothing, it only records what it's asked to do. It's used before the real
+// backend is set up and in tests, where a clone of it is kept to look into the state.
+#[derive(Clone, Default)]
+pub struct NullPlayer {
+    pub state: Rc<RefCell<NullState>>,
+}
+
+#[derive(Default)]
+pub struct NullState {
+    pub file: Option<String>,
+    pub paused: bool,
+    pub position_ms: u64,
+    pub volume: f64,
+    pub rate: f64,
+    pub equalizer: [f64; EQUALIZER_BANDS],
+}
+
+impl PlayerTrait for NullPlayer {
+    fn queue_and_play(&mut self, file: &str) {
+        let mut state = self.state.borrow_mut();
+        state.file = Some(file.to_string());
+        state.paused = false;
+        state.position_ms = 0;
+    }
+
+    fn queue_and_pause(&mut self, file: &str) {
+        let mut state = self.state.borrow_mut();
+        state.file = Some(file.to_string());
+        state.paused = true;
+        state.position_ms = 0;
+    }
+
+    fn pause(&mut self) {
+        self.state.borrow_mut().paused = true;
+    }
+
+    fn resume(&mut self) {
+        self.state.borrow_mut().paused = false;
+    }
+
+    fn seek_to_ms(&mut self, ms: u64) {
+        self.state.borrow_mut().position_ms = ms;
+    }
+
+    fn position_ms(&self) -> u64 {
+        self.state.borrow().position_ms
+    }
+
+    fn duration_ms(&self) -> Option<u64> {
+        None
+    }
+
+    fn set_volume(&mut self, volume: f64) {
+        self.state.borrow_mut().volume = volume;
+    }
+
+    fn next_msg(&mut self) -> Option<PlayerMsg> {
+        None
+    }
+
+    fn set_rate(&mut self, rate: f64) {
+        self.state.borrow_mut().rate = rate;
+    }
+
+    fn set_equalizer(&mut self, bands: &[f64; EQUALIZER_BANDS]) {
+        self.state.borrow_mut().equalizer = *bands;
+    }
+}
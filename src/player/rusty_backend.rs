@@ -0,0 +1,235 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use super::{is_stream, PlayerMsg, PlayerState, PlayerTrait};
+use anyhow::{anyhow, bail, Result};
+use rodio::cpal::traits::HostTrait;
+use rodio::{Decoder, DeviceTrait, OutputStream, OutputStreamHandle, Sink, Source};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufReader;
+use std::time::{Duration, Instant};
+
+// how often position updates are sent while playing
+const POSITION_INTERVAL: Duration = Duration::from_millis(100);
+
+// RustyBackend decodes mp3, flac, ogg and m4a in pure rust through rodio and symphonia. Rate,
+// equalizer, crossfade and gapless playback are not supported by it.
+pub struct RustyBackend {
+    // stream has to be kept alive as long as sounds are played on its handle
+    stream: Option<(OutputStream, OutputStreamHandle)>,
+    // a new sink is created for each song, dropping the old one stops it
+    sink: Option<Sink>,
+    file: Option<String>,
+    duration: Option<Duration>,
+    paused: bool,
+    volume: f64,
+    // end of stream is sent once for each song
+    finished: bool,
+    last_position_update: Instant,
+    messages: VecDeque<PlayerMsg>,
+}
+
+impl RustyBackend {
+    pub fn new() -> Self {
+        let stream = OutputStream::try_default()
+            .map_err(|e| log::error!("Failed to open audio output: {}", e))
+            .ok();
+        Self {
+            stream,
+            sink: None,
+            file: None,
+            duration: None,
+            paused: false,
+            volume: 0.5,
+            finished: false,
+            last_position_update: Instant::now(),
+            messages: VecDeque::new(),
+        }
+    }
+
+    fn decode(file: &str) -> Result<Decoder<BufReader<File>>> {
+        let reader = BufReader::new(File::open(file)?);
+        Ok(Decoder::new(reader)?)
+    }
+
+    pub fn duration(file: &str) -> Duration {
+        Self::decode(file)
+            .ok()
+            .and_then(|d| d.total_duration())
+            .unwrap_or_default()
+    }
+
+    // load replaces the sink with a new one playing file from the beginning
+    fn load(&mut self, file: &str, paused: bool) {
+        self.sink = None;
+        self.file = Some(file.to_string());
+        self.duration = None;
+        self.finished = false;
+        self.paused = paused;
+        if let Err(e) = self.try_load(file) {
+            self.messages
+                .push_back(PlayerMsg::Error(Some(file.to_string()), e.to_string()));
+            return;
+        }
+        self.messages.push_back(PlayerMsg::DurationChanged);
+        self.messages.push_back(PlayerMsg::StateChanged(if paused {
+            PlayerState::Paused
+        } else {
+            PlayerState::Playing
+        }));
+    }
+
+    fn try_load(&mut self, file: &str) -> Result<()> {
+        if is_stream(file) {
+            bail!("network streams are not supported by rusty backend");
+        }
+        let (_, handle) = self
+            .stream
+            .as_ref()
+            .ok_or_else(|| anyhow!("no audio output"))?;
+        let decoder = Self::decode(file)?;
+        self.duration = decoder.total_duration();
+        let sink = Sink::try_new(handle)?;
+        #[allow(clippy::cast_possible_truncation)]
+        sink.set_volume(self.volume as f32);
+        if self.paused {
+            sink.pause();
+        }
+        sink.append(decoder);
+        self.sink = Some(sink);
+        Ok(())
+    }
+
+    fn output_device(name: &str) -> Option<rodio::Device> {
+        rodio::cpal::default_host()
+            .output_devices()
+            .ok()?
+            .find(|d| d.name().is_ok_and(|n| n == name))
+    }
+}
+
+impl PlayerTrait for RustyBackend {
+    fn queue_and_play(&mut self, file: &str) {
+        self.load(file, false);
+    }
+
+    fn queue_and_pause(&mut self, file: &str) {
+        self.load(file, true);
+    }
+
+    fn pause(&mut self) {
+        self.paused = true;
+        if let Some(sink) = &self.sink {
+            sink.pause();
+            self.messages
+                .push_back(PlayerMsg::StateChanged(PlayerState::Paused));
+        }
+    }
+
+    fn resume(&mut self) {
+        self.paused = false;
+        if let Some(sink) = &self.sink {
+            sink.play();
+            self.messages
+                .push_back(PlayerMsg::StateChanged(PlayerState::Playing));
+        }
+    }
+
+    fn seek_to_ms(&mut self, ms: u64) {
+        if let Some(sink) = &self.sink {
+            if let Err(e) = sink.try_seek(Duration::from_millis(ms)) {
+                self.messages
+                    .push_back(PlayerMsg::Warning(format!("seek failed: {}", e)));
+            }
+            self.messages.push_back(PlayerMsg::PositionUpdated);
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn position_ms(&self) -> u64 {
+        self.sink
+            .as_ref()
+            .map_or(0, |s| s.get_pos().as_millis() as u64)
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn duration_ms(&self) -> Option<u64> {
+        self.duration.map(|d| d.as_millis() as u64)
+    }
+
+    fn set_volume(&mut self, volume: f64) {
+        self.volume = volume;
+        if let Some(sink) = &self.sink {
+            #[allow(clippy::cast_possible_truncation)]
+            sink.set_volume(volume as f32);
+        }
+    }
+
+    // next_msg polls the sink for end of stream and position, as rodio has no events
+    fn next_msg(&mut self) -> Option<PlayerMsg> {
+        if let Some(sink) = &self.sink {
+            if !self.finished && sink.empty() {
+                self.finished = true;
+                self.messages.push_back(PlayerMsg::EndOfStream);
+            } else if !self.paused
+                && !self.finished
+                && self.last_position_update.elapsed() >= POSITION_INTERVAL
+            {
+                self.last_position_update = Instant::now();
+                self.messages.push_back(PlayerMsg::PositionUpdated);
+            }
+        }
+        self.messages.pop_front()
+    }
+
+    fn output_devices(&self) -> Vec<String> {
+        rodio::cpal::default_host()
+            .output_devices()
+            .map(|devices| devices.filter_map(|d| d.name().ok()).collect())
+            .unwrap_or_default()
+    }
+
+    fn set_output_device(&mut self, name: Option<&str>) -> Result<()> {
+        let stream = match name {
+            Some(name) => {
+                let device = Self::output_device(name)
+                    .ok_or_else(|| anyhow!("output device {} not found", name))?;
+                OutputStream::try_from_device(&device)?
+            }
+            None => OutputStream::try_default()?,
+        };
+        self.stream = Some(stream);
+
+        // sink is bound to the old stream, so current song is loaded again
+        if let Some(file) = self.file.clone() {
+            if self.finished {
+                return Ok(());
+            }
+            let position = self.position_ms();
+            self.load(&file, self.paused);
+            self.seek_to_ms(position);
+        }
+        Ok(())
+    }
+}
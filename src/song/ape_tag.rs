@@ -0,0 +1,302 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+// Read and write APEv2 tags, as used by WavPack(.wv) and Monkey's Audio(.ape).
+// The tag sits at the end of the file, before an optional ID3v1 tag:
+// [header] items [footer] [ID3v1]
+// Header and footer are 32 bytes: "APETAGEX", version, size of items and footer,
+// item count, flags and 8 reserved bytes. All numbers are little endian.
+use anyhow::{bail, Result};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::convert::TryInto;
+use std::fs::{File, OpenOptions};
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+const APE_PREAMBLE: &[u8; 8] = b"APETAGEX";
+const APE_VERSION: u32 = 2000;
+const APE_HEADER_LEN: u64 = 32;
+const APE_HEADER_SIZE: usize = 32;
+const ID3V1_LEN: u64 = 128;
+// tag flags
+const HAS_HEADER: u32 = 1 << 31;
+const IS_HEADER: u32 = 1 << 29;
+// item flags, bits 1-2 hold the type of the value
+const ITEM_TYPE_MASK: u32 = 0b110;
+const ITEM_BINARY: u32 = 1 << 1;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApeItem {
+    pub key: String,
+    pub flags: u32,
+    pub value: Vec<u8>,
+}
+
+impl ApeItem {
+    pub const fn is_binary(&self) -> bool {
+        self.flags & ITEM_TYPE_MASK == ITEM_BINARY
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ApeTag {
+    items: Vec<ApeItem>,
+}
+
+// position of the tag in the file
+struct Location {
+    start: u64,
+    end: u64,
+    items: Option<(u64, u32, u64)>,
+}
+
+impl ApeTag {
+    pub fn read_from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let mut file = File::open(path)?;
+        let location = locate(&mut file)?;
+        let Some((items_start, count, items_len)) = location.items else {
+            bail!("no ape tag found")
+        };
+        let mut data = vec![0; items_len.try_into()?];
+        file.seek(SeekFrom::Start(items_start))?;
+        file.read_exact(&mut data)?;
+        Self::from_items(&data, count)
+    }
+
+    fn from_items(data: &[u8], count: u32) -> Result<Self> {
+        let mut cursor = Cursor::new(data);
+        let mut items = Vec::new();
+        for _ in 0..count {
+            let len = cursor.read_u32::<LittleEndian>()?;
+            let flags = cursor.read_u32::<LittleEndian>()?;
+            let mut key = Vec::new();
+            loop {
+                match cursor.read_u8()? {
+                    0 => break,
+                    b => key.push(b),
+                }
+            }
+            // len comes from the file, it must not be trusted before allocating
+            let remaining = (data.len() as u64).saturating_sub(cursor.position());
+            if u64::from(len) > remaining {
+                bail!("broken ape tag");
+            }
+            let mut value = vec![0; len.try_into()?];
+            cursor.read_exact(&mut value)?;
+            items.push(ApeItem {
+                key: String::from_utf8_lossy(&key).to_string(),
+                flags,
+                value,
+            });
+        }
+        Ok(Self { items })
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = &ApeItem> {
+        self.items.iter()
+    }
+
+    // keys are case insensitive in ape tags
+    fn position(&self, key: &str) -> Option<usize> {
+        self.items
+            .iter()
+            .position(|i| i.key.eq_ignore_ascii_case(key))
+    }
+
+    pub fn text(&self, key: &str) -> Option<String> {
+        self.position(key)
+            .map(|i| &self.items[i])
+            .filter(|i| !i.is_binary())
+            .map(|i| String::from_utf8_lossy(&i.value).to_string())
+    }
+
+    pub fn set_text(&mut self, key: &str, value: &str) {
+        self.set(key, 0, value.as_bytes().to_vec());
+    }
+
+    pub fn set_binary(&mut self, key: &str, value: Vec<u8>) {
+        self.set(key, ITEM_BINARY, value);
+    }
+
+    fn set(&mut self, key: &str, flags: u32, value: Vec<u8>) {
+        let item = ApeItem {
+            key: key.to_string(),
+            flags,
+            value,
+        };
+        match self.position(key) {
+            Some(i) => self.items[i] = item,
+            None => self.items.push(item),
+        }
+    }
+
+    pub fn retain(&mut self, f: impl FnMut(&ApeItem) -> bool) {
+        self.items.retain(f);
+    }
+
+    // write_to_path replaces the old tag in place, audio data and ID3v1 tag are kept
+    pub fn write_to_path(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+        let location = locate(&mut file)?;
+
+        let mut id3v1 = Vec::new();
+        file.seek(SeekFrom::Start(location.end))?;
+        file.read_to_end(&mut id3v1)?;
+
+        file.set_len(location.start)?;
+        file.seek(SeekFrom::End(0))?;
+        file.write_all(&self.as_bytes()?)?;
+        file.write_all(&id3v1)?;
+        Ok(())
+    }
+
+    fn as_bytes(&self) -> Result<Vec<u8>> {
+        let mut items = Vec::new();
+        for item in &self.items {
+            items.write_u32::<LittleEndian>(item.value.len().try_into()?)?;
+            items.write_u32::<LittleEndian>(item.flags)?;
+            items.extend(item.key.as_bytes());
+            items.push(0);
+            items.extend(&item.value);
+        }
+        let size: u32 = (items.len() + APE_HEADER_SIZE).try_into()?;
+        let count: u32 = self.items.len().try_into()?;
+
+        let mut bytes = Vec::new();
+        write_header(&mut bytes, size, count, HAS_HEADER | IS_HEADER)?;
+        bytes.extend(items);
+        write_header(&mut bytes, size, count, HAS_HEADER)?;
+        Ok(bytes)
+    }
+}
+
+fn write_header(bytes: &mut Vec<u8>, size: u32, count: u32, flags: u32) -> Result<()> {
+    bytes.extend(APE_PREAMBLE);
+    bytes.write_u32::<LittleEndian>(APE_VERSION)?;
+    bytes.write_u32::<LittleEndian>(size)?;
+    bytes.write_u32::<LittleEndian>(count)?;
+    bytes.write_u32::<LittleEndian>(flags)?;
+    bytes.extend([0; 8]);
+    Ok(())
+}
+
+// locate finds the tag by its footer, skipping a trailing ID3v1 tag
+fn locate(file: &mut File) -> Result<Location> {
+    let len = file.seek(SeekFrom::End(0))?;
+    let mut end = len;
+    if len >= ID3V1_LEN {
+        let mut magic = [0; 3];
+        file.seek(SeekFrom::Start(len - ID3V1_LEN))?;
+        file.read_exact(&mut magic)?;
+        if &magic == b"TAG" {
+            end = len - ID3V1_LEN;
+        }
+    }
+
+    if end < APE_HEADER_LEN {
+        return Ok(Location {
+            start: end,
+            end,
+            items: None,
+        });
+    }
+
+    let mut footer = [0; APE_HEADER_SIZE];
+    file.seek(SeekFrom::Start(end - APE_HEADER_LEN))?;
+    file.read_exact(&mut footer)?;
+    if &footer[..8] != APE_PREAMBLE {
+        return Ok(Location {
+            start: end,
+            end,
+            items: None,
+        });
+    }
+
+    let mut cursor = Cursor::new(&footer[8..]);
+    let _version = cursor.read_u32::<LittleEndian>()?;
+    let size = u64::from(cursor.read_u32::<LittleEndian>()?);
+    let count = cursor.read_u32::<LittleEndian>()?;
+    let flags = cursor.read_u32::<LittleEndian>()?;
+    if size < APE_HEADER_LEN || size > end {
+        bail!("broken ape tag");
+    }
+
+    let items_start = end - size;
+    let mut start = items_start;
+    if flags & HAS_HEADER != 0 {
+        start = start.saturating_sub(APE_HEADER_LEN);
+    }
+    Ok(Location {
+        start,
+        end,
+        items: Some((items_start, count, size - APE_HEADER_LEN)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_ape_tag_round_trip() {
+        let path = std::env::temp_dir().join("termusic_test_ape_tag.wv");
+        let audio = b"wvpk some audio data".to_vec();
+        let mut id3v1 = b"TAG".to_vec();
+        id3v1.resize(128, b' ');
+        std::fs::write(&path, [audio.clone(), id3v1.clone()].concat()).unwrap();
+
+        let mut tag = ApeTag::default();
+        tag.set_text("Artist", "Foo");
+        tag.set_text("Title", "Bar");
+        tag.set_binary("Cover Art (Front)", b"cover\0data".to_vec());
+        tag.write_to_path(&path).unwrap();
+
+        let mut tag = ApeTag::read_from_path(&path).unwrap();
+        assert_eq!(tag.text("ARTIST"), Some("Foo".to_string()));
+        assert_eq!(tag.text("Cover Art (Front)"), None);
+        tag.set_text("title", "Baz");
+        tag.retain(|i| !i.is_binary());
+        tag.write_to_path(&path).unwrap();
+
+        let tag = ApeTag::read_from_path(&path).unwrap();
+        assert_eq!(tag.text("Title"), Some("Baz".to_string()));
+        assert_eq!(tag.items().count(), 2);
+        let data = std::fs::read(&path).unwrap();
+        assert!(data.starts_with(&audio));
+        assert!(data.ends_with(&id3v1));
+        let _drop = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_ape_tag_broken_item() {
+        let mut data = u32::MAX.to_le_bytes().to_vec();
+        data.extend_from_slice(&0_u32.to_le_bytes());
+        data.extend_from_slice(b"Title\0Foo");
+        assert_eq!(
+            ApeTag::from_items(&data, 1).unwrap_err().to_string(),
+            "broken ape tag"
+        );
+    }
+}
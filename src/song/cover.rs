@@ -0,0 +1,124 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use anyhow::{anyhow, Result};
+use id3::frame::{Picture, PictureType};
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView, ImageFormat};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// file names of folder images, the earlier ones are preferred
+const COVER_NAMES: [&str; 3] = ["cover", "folder", "front"];
+const COVER_EXTENSIONS: [&str; 3] = ["jpg", "jpeg", "png"];
+
+/// Find the image of an album folder, like cover.jpg, folder.png or front.jpeg
+pub fn find_folder_cover(dir: &Path) -> Option<PathBuf> {
+    let mut found: Vec<(usize, PathBuf)> = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.is_file())
+        .filter_map(|p| cover_rank(&p).map(|rank| (rank, p)))
+        .collect();
+    found.sort();
+    found.into_iter().next().map(|(_, p)| p)
+}
+
+// cover_rank returns the position of the file name in COVER_NAMES
+fn cover_rank(path: &Path) -> Option<usize> {
+    let stem = path.file_stem()?.to_str()?.to_lowercase();
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    if !COVER_EXTENSIONS.contains(&ext.as_str()) {
+        return None;
+    }
+    COVER_NAMES.iter().position(|name| *name == stem)
+}
+
+/// Load an image file as front cover. With `max_size` set, images larger than that are scaled
+/// down and all but jpeg files are compressed as jpeg, so the tags don't bloat.
+pub fn load_cover(path: &Path, max_size: u32, quality: u8) -> Result<Picture> {
+    let data = fs::read(path)?;
+    let format = image::guess_format(&data)
+        .map_err(|e| anyhow!("{} is not an image: {}", path.display(), e))?;
+    let img = image::load_from_memory_with_format(&data, format)?;
+
+    let too_large = max_size > 0 && (img.width() > max_size || img.height() > max_size);
+    let (mime_type, data) = match format {
+        ImageFormat::Jpeg if !too_large => ("image/jpeg", data),
+        ImageFormat::Png if max_size == 0 => ("image/png", data),
+        _ if too_large => (
+            "image/jpeg",
+            encode_jpeg(
+                &img.resize(max_size, max_size, FilterType::Lanczos3),
+                quality,
+            )?,
+        ),
+        _ => ("image/jpeg", encode_jpeg(&img, quality)?),
+    };
+
+    Ok(Picture {
+        mime_type: mime_type.to_string(),
+        picture_type: PictureType::CoverFront,
+        description: String::new(),
+        data,
+    })
+}
+
+fn encode_jpeg(img: &DynamicImage, quality: u8) -> Result<Vec<u8>> {
+    let mut data = vec![];
+    JpegEncoder::new_with_quality(&mut data, quality.clamp(1, 100))
+        .encode_image(&img.to_rgb8())
+        .map_err(|e| anyhow!("encode jpeg error: {}", e))?;
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_folder_cover() {
+        let dir = std::env::temp_dir().join("termusic_test_folder_cover");
+        let _drop = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        assert_eq!(find_folder_cover(&dir), None);
+
+        let front = dir.join("Front.PNG");
+        DynamicImage::new_rgb8(400, 200).save(&front).unwrap();
+        fs::write(dir.join("cover.txt"), "not an image").unwrap();
+        assert_eq!(find_folder_cover(&dir), Some(front.clone()));
+
+        let picture = load_cover(&front, 0, 85).unwrap();
+        assert_eq!(picture.mime_type, "image/png");
+        assert_eq!(picture.data, fs::read(&front).unwrap());
+
+        let picture = load_cover(&front, 100, 85).unwrap();
+        assert_eq!(picture.mime_type, "image/jpeg");
+        let img = image::load_from_memory(&picture.data).unwrap();
+        assert_eq!(img.dimensions(), (100, 50));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,372 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+// Extended tag fields, besides artist, title and album. Vorbis comments(flac, ogg and
+// opus) and ape tags are plain key value pairs, id3 and mp4 have their own frames and atoms.
+// Track and disc are written as separate number and total in vorbis comments:
+// TRACKNUMBER=3
+// TRACKTOTAL=12
+// and as a pair in ape tags:
+// Track=3/12
+use id3::frame::Comment;
+use id3::Timestamp;
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+pub const VORBIS_KEYS: [&str; 14] = [
+    "ALBUMARTIST",
+    "ALBUM ARTIST",
+    "COMPOSER",
+    "GENRE",
+    "DATE",
+    "YEAR",
+    "COMMENT",
+    "DESCRIPTION",
+    "TRACKNUMBER",
+    "TRACKTOTAL",
+    "TOTALTRACKS",
+    "DISCNUMBER",
+    "DISCTOTAL",
+    "TOTALDISCS",
+];
+
+pub const APE_KEYS: [&str; 8] = [
+    "ALBUM ARTIST",
+    "ALBUMARTIST",
+    "COMPOSER",
+    "GENRE",
+    "YEAR",
+    "COMMENT",
+    "TRACK",
+    "DISC",
+];
+
+// id3 frame of the composer, there is no accessor for it
+const ID3_COMPOSER: &str = "TCOM";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TagFields {
+    pub album_artist: Option<String>,
+    pub composer: Option<String>,
+    pub genre: Option<String>,
+    /// year or full date, like 2021 or 2021-09-13
+    pub year: Option<String>,
+    pub comment: Option<String>,
+    pub track: Option<u32>,
+    pub track_total: Option<u32>,
+    pub disc: Option<u32>,
+    pub disc_total: Option<u32>,
+}
+
+impl TagFields {
+    pub fn set_vorbis(&mut self, key: &str, value: &str) {
+        match key.to_uppercase().as_str() {
+            "ALBUMARTIST" | "ALBUM ARTIST" => self.album_artist = parse_text(value),
+            "COMPOSER" => self.composer = parse_text(value),
+            "GENRE" => self.genre = parse_text(value),
+            "DATE" | "YEAR" => self.year = parse_text(value),
+            "COMMENT" | "DESCRIPTION" => self.comment = parse_text(value),
+            "TRACKNUMBER" => self.merge_track(value),
+            "TRACKTOTAL" | "TOTALTRACKS" => self.track_total = parse_number(value),
+            "DISCNUMBER" => self.merge_disc(value),
+            "DISCTOTAL" | "TOTALDISCS" => self.disc_total = parse_number(value),
+            _ => {}
+        }
+    }
+
+    pub fn as_vorbis(&self) -> Vec<(&'static str, String)> {
+        let mut tags = self.texts(["ALBUMARTIST", "COMPOSER", "GENRE", "DATE", "COMMENT"]);
+        let numbers = [
+            ("TRACKNUMBER", self.track),
+            ("TRACKTOTAL", self.track_total),
+            ("DISCNUMBER", self.disc),
+            ("DISCTOTAL", self.disc_total),
+        ];
+        for (key, value) in numbers {
+            if let Some(v) = value {
+                tags.push((key, v.to_string()));
+            }
+        }
+        tags
+    }
+
+    pub fn set_ape(&mut self, key: &str, value: &str) {
+        match key.to_uppercase().as_str() {
+            "ALBUM ARTIST" | "ALBUMARTIST" => self.album_artist = parse_text(value),
+            "COMPOSER" => self.composer = parse_text(value),
+            "GENRE" => self.genre = parse_text(value),
+            "YEAR" => self.year = parse_text(value),
+            "COMMENT" => self.comment = parse_text(value),
+            "TRACK" => self.merge_track(value),
+            "DISC" => self.merge_disc(value),
+            _ => {}
+        }
+    }
+
+    pub fn as_ape(&self) -> Vec<(&'static str, String)> {
+        let mut tags = self.texts(["Album Artist", "Composer", "Genre", "Year", "Comment"]);
+        if self.track.is_some() {
+            tags.push(("Track", self.track_pair()));
+        }
+        if self.disc.is_some() {
+            tags.push(("Disc", self.disc_pair()));
+        }
+        tags
+    }
+
+    // texts pairs the keys with album artist, composer, genre, year and comment
+    fn texts(&self, keys: [&'static str; 5]) -> Vec<(&'static str, String)> {
+        let values = [
+            &self.album_artist,
+            &self.composer,
+            &self.genre,
+            &self.year,
+            &self.comment,
+        ];
+        keys.iter()
+            .zip(values.iter())
+            .filter_map(|(key, value)| value.as_ref().map(|v| (*key, v.clone())))
+            .collect()
+    }
+
+    pub fn from_id3(tag: &id3::Tag) -> Self {
+        Self {
+            album_artist: tag.album_artist().map(String::from),
+            composer: tag
+                .get(ID3_COMPOSER)
+                .and_then(|f| f.content().text())
+                .map(String::from),
+            genre: tag.genre().map(String::from),
+            year: tag
+                .date_recorded()
+                .map(|d| d.to_string())
+                .or_else(|| tag.year().map(|y| y.to_string())),
+            comment: tag
+                .comments()
+                .find(|c| c.description.is_empty())
+                .or_else(|| tag.comments().next())
+                .map(|c| c.text.clone()),
+            track: tag.track(),
+            track_total: tag.total_tracks(),
+            disc: tag.disc(),
+            disc_total: tag.total_discs(),
+        }
+    }
+
+    pub fn write_id3(&self, tag: &mut id3::Tag) {
+        match &self.album_artist {
+            Some(v) => tag.set_album_artist(v),
+            None => tag.remove_album_artist(),
+        }
+        match &self.composer {
+            Some(v) => tag.set_text(ID3_COMPOSER, v),
+            None => tag.remove(ID3_COMPOSER),
+        }
+        match &self.genre {
+            Some(v) => tag.set_genre(v),
+            None => tag.remove_genre(),
+        }
+        // id3v2.4 keeps the date in TDRC, TYER is from v2.3
+        tag.remove_year();
+        match self.year.as_deref().map(Timestamp::from_str) {
+            Some(Ok(t)) => tag.set_date_recorded(t),
+            _ => tag.remove_date_recorded(),
+        }
+        tag.remove_comment(Some(""), None);
+        if let Some(v) = &self.comment {
+            tag.add_comment(Comment {
+                lang: "eng".to_string(),
+                description: String::new(),
+                text: v.clone(),
+            });
+        }
+        match self.track {
+            Some(v) => tag.set_track(v),
+            None => tag.remove_track(),
+        }
+        match self.track_total {
+            Some(v) => tag.set_total_tracks(v),
+            None => tag.remove_total_tracks(),
+        }
+        match self.disc {
+            Some(v) => tag.set_disc(v),
+            None => tag.remove_disc(),
+        }
+        match self.disc_total {
+            Some(v) => tag.set_total_discs(v),
+            None => tag.remove_total_discs(),
+        }
+    }
+
+    pub fn from_mp4(tag: &mp4ameta::Tag) -> Self {
+        Self {
+            album_artist: tag.album_artist().map(String::from),
+            composer: tag.composer().map(String::from),
+            genre: tag.genre().map(String::from),
+            year: tag.year().map(String::from),
+            comment: tag.comment().map(String::from),
+            track: tag.track_number().map(u32::from),
+            track_total: tag.total_tracks().map(u32::from),
+            disc: tag.disc_number().map(u32::from),
+            disc_total: tag.total_discs().map(u32::from),
+        }
+    }
+
+    pub fn write_mp4(&self, tag: &mut mp4ameta::Tag) {
+        match &self.album_artist {
+            Some(v) => tag.set_album_artist(v),
+            None => tag.remove_album_artists(),
+        }
+        match &self.composer {
+            Some(v) => tag.set_composer(v),
+            None => tag.remove_composers(),
+        }
+        match &self.genre {
+            Some(v) => tag.set_genre(v),
+            None => tag.remove_genres(),
+        }
+        match &self.year {
+            Some(v) => tag.set_year(v),
+            None => tag.remove_year(),
+        }
+        match &self.comment {
+            Some(v) => tag.set_comment(v),
+            None => tag.remove_comments(),
+        }
+        // mp4 keeps number and total in one atom, zero means unknown
+        tag.remove_track();
+        if self.track.is_some() || self.track_total.is_some() {
+            tag.set_track(to_u16(self.track), to_u16(self.track_total));
+        }
+        tag.remove_disc();
+        if self.disc.is_some() || self.disc_total.is_some() {
+            tag.set_disc(to_u16(self.disc), to_u16(self.disc_total));
+        }
+    }
+
+    /// Track as shown in the tag editor, like 3/12
+    pub fn track_pair(&self) -> String {
+        format_pair(self.track, self.track_total)
+    }
+
+    /// Set track from the tag editor, both number and total are replaced
+    pub fn set_track_pair(&mut self, value: &str) {
+        let (number, total) = parse_pair(value);
+        self.track = number;
+        self.track_total = total;
+    }
+
+    // merge_track keeps the total read from another key, when the value has none
+    fn merge_track(&mut self, value: &str) {
+        let (number, total) = parse_pair(value);
+        self.track = number;
+        if total.is_some() {
+            self.track_total = total;
+        }
+    }
+
+    pub fn disc_pair(&self) -> String {
+        format_pair(self.disc, self.disc_total)
+    }
+
+    pub fn set_disc_pair(&mut self, value: &str) {
+        let (number, total) = parse_pair(value);
+        self.disc = number;
+        self.disc_total = total;
+    }
+
+    fn merge_disc(&mut self, value: &str) {
+        let (number, total) = parse_pair(value);
+        self.disc = number;
+        if total.is_some() {
+            self.disc_total = total;
+        }
+    }
+}
+
+// empty text means the field is removed
+pub fn parse_text(value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.is_empty() {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+fn parse_number(value: &str) -> Option<u32> {
+    value.trim().parse().ok()
+}
+
+fn parse_pair(value: &str) -> (Option<u32>, Option<u32>) {
+    match value.split_once('/') {
+        Some((number, total)) => (parse_number(number), parse_number(total)),
+        None => (parse_number(value), None),
+    }
+}
+
+fn format_pair(number: Option<u32>, total: Option<u32>) -> String {
+    match (number, total) {
+        (Some(n), Some(t)) => format!("{}/{}", n, t),
+        (Some(n), None) => n.to_string(),
+        (None, Some(t)) => format!("/{}", t),
+        (None, None) => String::new(),
+    }
+}
+
+fn to_u16(value: Option<u32>) -> u16 {
+    value
+        .and_then(|v| u16::try_from(v).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_vorbis_fields() {
+        let mut fields = TagFields::default();
+        fields.set_vorbis("albumartist", "Foo");
+        fields.set_vorbis("TRACKNUMBER", "3/12");
+        fields.set_vorbis("DISCNUMBER", "1");
+        fields.set_vorbis("DISCTOTAL", "2");
+        fields.set_vorbis("GENRE", " ");
+        assert_eq!(fields.album_artist, Some("Foo".to_string()));
+        assert_eq!(fields.genre, None);
+        assert_eq!(fields.track_pair(), "3/12");
+        assert_eq!(fields.disc_pair(), "1/2");
+        assert_eq!(
+            fields.as_vorbis(),
+            vec![
+                ("ALBUMARTIST", "Foo".to_string()),
+                ("TRACKNUMBER", "3".to_string()),
+                ("TRACKTOTAL", "12".to_string()),
+                ("DISCNUMBER", "1".to_string()),
+                ("DISCTOTAL", "2".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,203 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+// Detect the format of a file by its content, the extension is only used when the
+// magic bytes are unknown.
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+const ID3_HEADER_LEN: u64 = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileFormat {
+    Mp3,
+    M4a,
+    Flac,
+    Ogg,
+    Opus,
+    Wav,
+    Aiff,
+    WavPack,
+    Ape,
+}
+
+impl fmt::Display for FileFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.ext())
+    }
+}
+
+impl FileFormat {
+    /// Format of the file, by magic bytes first and then by extension
+    pub fn detect(path: &str) -> Option<Self> {
+        Self::from_content(path).or_else(|| Self::from_path_ext(path))
+    }
+
+    pub fn from_path_ext(path: &str) -> Option<Self> {
+        Path::new(path)
+            .extension()
+            .and_then(OsStr::to_str)
+            .and_then(Self::from_ext)
+    }
+
+    // extensions are case insensitive, .MP3 and .Flac are fine
+    pub fn from_ext(ext: &str) -> Option<Self> {
+        match ext.to_lowercase().as_str() {
+            "mp3" => Some(Self::Mp3),
+            "m4a" | "mp4" | "m4b" => Some(Self::M4a),
+            "flac" => Some(Self::Flac),
+            "ogg" | "oga" => Some(Self::Ogg),
+            "opus" => Some(Self::Opus),
+            "wav" => Some(Self::Wav),
+            "aiff" | "aif" => Some(Self::Aiff),
+            "wv" => Some(Self::WavPack),
+            "ape" => Some(Self::Ape),
+            _ => None,
+        }
+    }
+
+    /// The usual extension of the format
+    pub const fn ext(self) -> &'static str {
+        match self {
+            Self::Mp3 => "mp3",
+            Self::M4a => "m4a",
+            Self::Flac => "flac",
+            Self::Ogg => "ogg",
+            Self::Opus => "opus",
+            Self::Wav => "wav",
+            Self::Aiff => "aiff",
+            Self::WavPack => "wv",
+            Self::Ape => "ape",
+        }
+    }
+
+    pub fn from_content(path: &str) -> Option<Self> {
+        let mut file = File::open(path).ok()?;
+        let mut head = [0; 64];
+        let len = read_head(&mut file, &mut head)?;
+        let head = &head[..len];
+
+        // flac and ape could have an id3v2 tag in front, skip it
+        if head.starts_with(b"ID3") && len >= 10 {
+            let size = head[6..10]
+                .iter()
+                .fold(0_u64, |acc, b| (acc << 7) | u64::from(b & 0x7f));
+            let footer = if head[5] & 0x10 == 0 { 0 } else { 10 };
+            file.seek(SeekFrom::Start(ID3_HEADER_LEN + size + footer))
+                .ok()?;
+            let mut after = [0; 64];
+            let len = read_head(&mut file, &mut after)?;
+            return Some(Self::from_magic(&after[..len]).unwrap_or(Self::Mp3));
+        }
+
+        Self::from_magic(head)
+    }
+
+    pub fn from_magic(head: &[u8]) -> Option<Self> {
+        match head {
+            [b'f', b'L', b'a', b'C', ..] => Some(Self::Flac),
+            [b'O', b'g', b'g', b'S', ..] => Some(ogg_format(head)),
+            [_, _, _, _, b'f', b't', b'y', b'p', ..] => Some(Self::M4a),
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => Some(Self::Wav),
+            [b'F', b'O', b'R', b'M', _, _, _, _, b'A', b'I', b'F', b'F' | b'C', ..] => {
+                Some(Self::Aiff)
+            }
+            [b'w', b'v', b'p', b'k', ..] => Some(Self::WavPack),
+            [b'M', b'A', b'C', b' ', ..] => Some(Self::Ape),
+            [b'I', b'D', b'3', ..] => Some(Self::Mp3),
+            // mpeg audio frame sync, layer bits must not be zero
+            [0xff, b, ..] if b & 0xe0 == 0xe0 && b & 0x06 != 0 => Some(Self::Mp3),
+            _ => None,
+        }
+    }
+}
+
+fn read_head(file: &mut File, buf: &mut [u8]) -> Option<usize> {
+    let mut len = 0;
+    while len < buf.len() {
+        match file.read(&mut buf[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(_) => return None,
+        }
+    }
+    Some(len)
+}
+
+// the first packet of the first page tells the codec, it starts after the
+// 27 bytes page header and the segment table
+fn ogg_format(head: &[u8]) -> FileFormat {
+    let packet = head
+        .get(26)
+        .and_then(|segments| head.get(27 + usize::from(*segments)..));
+    match packet {
+        Some(p) if p.starts_with(b"OpusHead") => FileFormat::Opus,
+        _ => FileFormat::Ogg,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_from_magic() {
+        assert_eq!(
+            FileFormat::from_magic(b"fLaC\0\0\0\x22"),
+            Some(FileFormat::Flac)
+        );
+        assert_eq!(
+            FileFormat::from_magic(b"\0\0\0\x20ftypM4A "),
+            Some(FileFormat::M4a)
+        );
+        assert_eq!(
+            FileFormat::from_magic(b"RIFF\x24\0\0\0WAVEfmt "),
+            Some(FileFormat::Wav)
+        );
+        assert_eq!(
+            FileFormat::from_magic(&[0xff, 0xfb, 0x90]),
+            Some(FileFormat::Mp3)
+        );
+        // adts aac has layer bits set to zero
+        assert_eq!(FileFormat::from_magic(&[0xff, 0xf1, 0x50]), None);
+
+        let mut opus = b"OggS".to_vec();
+        opus.resize(26, 0);
+        opus.push(1);
+        opus.push(19);
+        opus.extend(b"OpusHead");
+        assert_eq!(FileFormat::from_magic(&opus), Some(FileFormat::Opus));
+    }
+
+    #[test]
+    fn test_from_ext() {
+        assert_eq!(FileFormat::from_ext("MP3"), Some(FileFormat::Mp3));
+        assert_eq!(FileFormat::from_ext("Flac"), Some(FileFormat::Flac));
+        assert_eq!(FileFormat::from_ext("txt"), None);
+    }
+}
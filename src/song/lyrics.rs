@@ -0,0 +1,149 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+// Tags other than id3 have no language or description of lyrics. The first lyrics are written
+// under the plain key for other players, and all lyrics under keys like LYRICS:netease.
+use id3::frame::Lyrics;
+
+pub const VORBIS_LYRICS: &str = "LYRICS";
+pub const APE_LYRICS: &str = "Lyrics";
+// keys of unsynced lyrics written by other taggers as well
+const PLAIN_KEYS: [&str; 3] = ["LYRICS", "UNSYNCEDLYRICS", "UNSYNCED LYRICS"];
+const DEFAULT_DESCRIPTION: &str = "termusic";
+
+/// `LyricsReader` collects lyrics from the items of a tag
+#[derive(Default)]
+pub struct LyricsReader {
+    plain: Vec<Lyrics>,
+    described: Vec<Lyrics>,
+}
+
+impl LyricsReader {
+    // add returns false if the key is not of lyrics
+    pub fn add(&mut self, key: &str, value: &str) -> bool {
+        let (name, description) = split_key(key);
+        if !is_plain_key(name) {
+            return false;
+        }
+        let lyrics = Lyrics {
+            lang: "eng".to_string(),
+            description: description.unwrap_or(DEFAULT_DESCRIPTION).to_string(),
+            text: value.to_string(),
+        };
+        match description {
+            Some(_) => self.described.push(lyrics),
+            None => self.plain.push(lyrics),
+        }
+        true
+    }
+
+    // finish returns the described lyrics, or the plain ones if there are none
+    pub fn finish(self) -> Vec<Lyrics> {
+        let mut lyrics = if self.described.is_empty() {
+            self.plain
+        } else {
+            self.described
+        };
+        lyrics.sort_by_cached_key(|l| l.description.clone());
+        lyrics
+    }
+}
+
+/// Whether the item of a tag holds lyrics
+pub fn is_lyrics_key(key: &str) -> bool {
+    is_plain_key(split_key(key).0)
+}
+
+// split_key splits a key like LYRICS:netease into name and description
+fn split_key(key: &str) -> (&str, Option<&str>) {
+    match key.split_once(':') {
+        Some((name, description)) => (name, Some(description)),
+        None => (key, None),
+    }
+}
+
+fn is_plain_key(name: &str) -> bool {
+    PLAIN_KEYS.iter().any(|k| k.eq_ignore_ascii_case(name))
+}
+
+/// Keys and texts to write, the first lyrics go under the plain key as well
+pub fn lyrics_items(key: &str, lyric_frames: &[Lyrics]) -> Vec<(String, String)> {
+    let mut items: Vec<(String, String)> = lyric_frames
+        .first()
+        .map(|l| (key.to_string(), l.text.clone()))
+        .into_iter()
+        .collect();
+    for l in lyric_frames {
+        // vorbis and ape keys are printable ascii without '='
+        let description: String = l
+            .description
+            .chars()
+            .map(|c| match c {
+                ' '..='}' if c != '=' => c,
+                _ => '_',
+            })
+            .collect();
+        items.push((format!("{}:{}", key, description), l.text.clone()));
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_lyrics_items() {
+        let lyric = |description: &str, text: &str| Lyrics {
+            lang: "eng".to_string(),
+            description: description.to_string(),
+            text: text.to_string(),
+        };
+        let items = lyrics_items(VORBIS_LYRICS, &[lyric("zh=Hans", "a"), lyric("en", "b")]);
+        assert_eq!(
+            items,
+            vec![
+                ("LYRICS".to_string(), "a".to_string()),
+                ("LYRICS:zh_Hans".to_string(), "a".to_string()),
+                ("LYRICS:en".to_string(), "b".to_string()),
+            ]
+        );
+
+        let mut reader = LyricsReader::default();
+        for (key, value) in &items {
+            assert!(reader.add(key, value));
+        }
+        assert!(!reader.add("LYRICIST", "c"));
+        let lyrics = reader.finish();
+        assert_eq!(lyrics.len(), 2);
+        assert_eq!(lyrics[0].description, "en");
+        assert_eq!(lyrics[1].text, "a");
+
+        // lyrics of other taggers
+        let mut reader = LyricsReader::default();
+        assert!(reader.add("unsyncedlyrics", "d"));
+        assert_eq!(reader.finish()[0].description, DEFAULT_DESCRIPTION);
+    }
+}
@@ -21,19 +21,36 @@
  * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
  * SOFTWARE.
  */
+mod ape_tag;
+mod cover;
+mod fields;
+mod format;
+mod lyrics;
 mod ogg_picture;
 mod ogg_reader_writer;
+pub mod replaygain;
+mod sylt;
 
-use crate::player::GStreamer;
+use crate::player;
 use crate::songtag::lrc::Lyric;
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
+use ape_tag::ApeTag;
+pub use cover::{find_folder_cover, load_cover};
+pub use fields::TagFields;
+pub use format::FileFormat;
 use humantime::{format_duration, FormattedDuration};
 use id3::frame::{Lyrics, Picture, PictureType};
+use lyrics::{is_lyrics_key, lyrics_items, LyricsReader, APE_LYRICS, VORBIS_LYRICS};
 use metaflac::Tag as FlacTag;
 use mp4ameta::{Img, ImgFmt};
-use ogg_picture::{MimeType, PictureType as OggPictureType};
-use ogg_reader_writer::{replace_comment_header, CommentHeader, VorbisComments};
+use ogg_picture::{MimeType, OggPicture, PicType, PictureType as OggPictureType};
+use ogg_reader_writer::{
+    read_opus_comment_header, replace_comment_header, replace_opus_comment_header, CommentHeader,
+    VorbisComments,
+};
+use replaygain::{ReplayGain, MP4_FREEFORM_MEAN, REPLAYGAIN_KEYS};
 // use lofty::{AudioTagEdit, AudioTagWrite, TagType};
+use std::convert::TryFrom;
 use std::ffi::OsStr;
 use std::fs::{rename, File};
 use std::io::{Cursor, Read};
@@ -41,6 +58,11 @@ use std::path::Path;
 use std::str::FromStr;
 use std::time::Duration;
 
+// keys of apev2 items, they are case insensitive
+const APE_ARTIST: &str = "Artist";
+const APE_TITLE: &str = "Title";
+const APE_ALBUM: &str = "Album";
+
 #[derive(Clone)]
 pub struct Song {
     /// Artist of the song
@@ -57,12 +79,19 @@ pub struct Song {
     name: Option<String>,
     /// Extension of the song
     ext: Option<String>,
-    // / uslt lyrics
+    /// Format of the song, detected by content
+    format: Option<FileFormat>,
+    /// track, disc, year, genre and the like
+    fields: TagFields,
+    /// lyrics in lrc format, read from USLT and SYLT frames of id3 or lyrics items of others
     pub lyric_frames: Vec<Lyrics>,
     pub lyric_selected: usize,
     pub parsed_lyric: Option<Lyric>,
     // pub lyrics: Option<String>,
-    pub picture: Option<Picture>,
+    /// embedded pictures, like front cover, back cover and artist
+    pub pictures: Vec<Picture>,
+    /// replaygain tags of the song
+    replaygain: ReplayGain,
 }
 
 impl Song {
@@ -108,6 +137,11 @@ impl Song {
         }
     }
 
+    /// Whether the song is a network stream, like a radio station
+    pub fn is_stream(&self) -> bool {
+        self.file().is_some_and(player::is_stream)
+    }
+
     fn ext(&self) -> Option<&str> {
         match self.ext.as_ref() {
             Some(ext) => Some(ext),
@@ -115,6 +149,46 @@ impl Song {
         }
     }
 
+    pub const fn fields(&self) -> &TagFields {
+        &self.fields
+    }
+
+    pub fn set_fields(&mut self, fields: TagFields) {
+        self.fields = fields;
+    }
+
+    // format_mismatch returns the format of the content, if the extension says otherwise
+    pub fn format_mismatch(&self) -> Option<FileFormat> {
+        let format = self.format?;
+        let ext_format = self.ext().and_then(FileFormat::from_ext);
+        (ext_format != Some(format)).then_some(format)
+    }
+
+    // fix_extension renames the file with the extension of its content. An existing file with
+    // the new name is never replaced.
+    pub fn fix_extension(&mut self) -> Result<()> {
+        let format = self
+            .format_mismatch()
+            .ok_or_else(|| anyhow!("extension matches the content already"))?;
+        let file = self.file().ok_or_else(|| anyhow!("no file found"))?;
+        let p_new = Path::new(file).with_extension(format.ext());
+        if p_new.exists() {
+            bail!("{} exists already", p_new.display());
+        }
+        rename(file, &p_new)?;
+        self.file = Some(String::from(p_new.to_string_lossy()));
+        self.name = p_new
+            .file_name()
+            .and_then(OsStr::to_str)
+            .map(std::string::ToString::to_string);
+        self.ext = Some(format.ext().to_string());
+        Ok(())
+    }
+
+    pub const fn replaygain(&self) -> &ReplayGain {
+        &self.replaygain
+    }
+
     pub const fn duration(&self) -> Duration {
         self.duration
     }
@@ -136,64 +210,115 @@ impl Song {
     pub fn update_duration(&self) -> Result<()> {
         let s = self.file().ok_or_else(|| anyhow!("no file found"))?;
 
-        if let Some("mp3") = self.ext() {
-            let mut id3_tag = id3::Tag::new();
-            if let Ok(t) = id3::Tag::read_from_path(s) {
-                id3_tag = t;
-            }
+        if self.format == Some(FileFormat::Mp3) {
+            let mut id3_tag = sylt::read_id3(s, FileFormat::Mp3).unwrap_or_default();
 
-            let duration_player = GStreamer::duration(s);
+            let duration_player = player::duration(s);
             id3_tag.remove_duration();
-            id3_tag.set_duration((duration_player.mseconds()) as u32);
+            id3_tag.set_duration(duration_player.as_millis() as u32);
             let _drop = id3_tag.write_to_path(s, id3::Version::Id3v24);
         }
         Ok(())
     }
 
     pub fn save_tag(&mut self) -> Result<()> {
-        match self.ext() {
-            Some("mp3") => self.save_mp3_tag()?,
-            Some("m4a") => self.save_m4a_tag()?,
-            Some("flac") => self.save_flac_tag()?,
-            Some("ogg") => self.save_ogg_tag()?,
-            _ => return Ok(()),
+        if self.format.is_none() {
+            return Ok(());
         }
-
+        self.write_tag()?;
         self.rename_by_tag()?;
 
         Ok(())
     }
 
-    fn save_mp3_tag(&self) -> Result<()> {
-        let mut id3_tag = id3::Tag::default();
-        if let Some(file) = self.file() {
-            if let Ok(t) = id3::Tag::read_from_path(file) {
-                id3_tag = t;
-            }
+    // save_replaygain writes the replaygain tags only, the file is not renamed
+    pub fn save_replaygain(&mut self, replaygain: ReplayGain) -> Result<()> {
+        self.replaygain = replaygain;
+        self.write_tag()
+    }
+
+    // save_cover embeds the picture and writes the tag, the file is not renamed
+    pub fn save_cover(&mut self, picture: Picture) -> Result<()> {
+        self.set_photo(picture);
+        self.write_tag()
+    }
+
+    /// Write the tags, the file is not renamed
+    pub fn write_tag(&self) -> Result<()> {
+        match self.format {
+            Some(FileFormat::Mp3) => self.save_mp3_tag(),
+            Some(FileFormat::M4a) => self.save_m4a_tag(),
+            Some(FileFormat::Flac) => self.save_flac_tag(),
+            Some(FileFormat::Ogg) => self.save_ogg_tag(),
+            Some(FileFormat::Opus) => self.save_opus_tag(),
+            Some(FileFormat::Wav) => self.save_wav_tag(),
+            Some(FileFormat::Aiff) => self.save_aiff_tag(),
+            Some(FileFormat::WavPack | FileFormat::Ape) => self.save_ape_tag(),
+            None => bail!("tag is not supported for this format"),
         }
+    }
 
+    fn save_mp3_tag(&self) -> Result<()> {
+        let file = self.file().ok_or_else(|| anyhow!("no file found"))?;
+        let id3_tag = self.id3_tag(sylt::read_id3(file, FileFormat::Mp3).unwrap_or_default());
+        id3_tag
+            .write_to_path(file, id3::Version::Id3v24)
+            .map_err(|e| anyhow!("write mp3 tag error {:?}", e))?;
+        Ok(())
+    }
+
+    // wav and aiff keep an id3 tag in a chunk of the file
+    fn save_wav_tag(&self) -> Result<()> {
+        let file = self.file().ok_or_else(|| anyhow!("no file found"))?;
+        let id3_tag = self.id3_tag(sylt::read_id3(file, FileFormat::Wav).unwrap_or_default());
+        id3_tag
+            .write_to_wav(file, id3::Version::Id3v24)
+            .map_err(|e| anyhow!("write wav tag error {:?}", e))?;
+        Ok(())
+    }
+
+    fn save_aiff_tag(&self) -> Result<()> {
+        let file = self.file().ok_or_else(|| anyhow!("no file found"))?;
+        let id3_tag = self.id3_tag(sylt::read_id3(file, FileFormat::Aiff).unwrap_or_default());
+        id3_tag
+            .write_to_aiff(file, id3::Version::Id3v24)
+            .map_err(|e| anyhow!("write aiff tag error {:?}", e))?;
+        Ok(())
+    }
+
+    // id3_tag updates the existing tag with the fields of the song
+    fn id3_tag(&self, mut id3_tag: id3::Tag) -> id3::Tag {
         id3_tag.set_artist(self.artist().unwrap_or(&String::from("Unknown Artist")));
         id3_tag.set_title(self.title().unwrap_or(&String::from("Unknown Title")));
         id3_tag.set_album(self.album().unwrap_or(&String::from("Unknown Album")));
         id3_tag.remove_all_lyrics();
-
-        if !self.lyric_frames.is_empty() {
-            let lyric_frames = self.lyric_frames.clone();
-            for l in lyric_frames {
-                id3_tag.add_lyrics(l);
+        id3_tag.remove_all_synchronised_lyrics();
+        for l in &self.lyric_frames {
+            id3_tag.add_lyrics(l.clone());
+            // lyrics with time stamps are kept as SYLT as well
+            if let Some(frame) = sylt::sylt_frame(l) {
+                id3_tag.add_frame(frame);
             }
         }
 
-        if let Some(p) = &self.picture {
+        id3_tag.remove_all_pictures();
+        for p in &self.pictures {
             id3_tag.add_picture(p.clone());
         }
 
-        if let Some(file) = self.file() {
-            id3_tag
-                .write_to_path(file, id3::Version::Id3v24)
-                .map_err(|e| anyhow!("write mp3 tag error {:?}", e))?;
+        let replaygain_texts: Vec<String> = id3_tag
+            .extended_texts()
+            .filter(|t| REPLAYGAIN_KEYS.contains(&t.description.to_uppercase().as_str()))
+            .map(|t| t.description.clone())
+            .collect();
+        for description in replaygain_texts {
+            id3_tag.remove_extended_text(Some(&description), None);
         }
-        Ok(())
+        for (key, value) in self.replaygain.as_tags() {
+            id3_tag.add_extended_text(key, value);
+        }
+        self.fields.write_id3(&mut id3_tag);
+        id3_tag
     }
 
     fn save_m4a_tag(&self) -> Result<()> {
@@ -212,28 +337,45 @@ impl Song {
                 .unwrap_or(&String::from("Unknown Album")),
         );
         m4a_tag.remove_lyrics();
-
-        if !self.lyric_frames.is_empty() {
-            let lyric_frames = self.lyric_frames.clone();
-            for l in lyric_frames {
-                m4a_tag.set_lyrics(l.text);
+        m4a_tag.retain_data(|ident, _| match ident {
+            mp4ameta::DataIdent::Freeform { mean, name } => {
+                !(mean == MP4_FREEFORM_MEAN && is_lyrics_key(name))
+            }
+            mp4ameta::DataIdent::Fourcc(_) => true,
+        });
+        // ©lyr holds the first lyrics, all of them are kept in freeform items
+        for (key, text) in lyrics_items(VORBIS_LYRICS, &self.lyric_frames) {
+            if key == VORBIS_LYRICS {
+                m4a_tag.set_lyrics(text);
+            } else {
+                let ident = mp4ameta::FreeformIdent::new(MP4_FREEFORM_MEAN, &key);
+                m4a_tag.add_data(ident, mp4ameta::Data::Utf8(text));
             }
         }
 
-        if let Some(p) = &self.picture {
+        // mp4 artworks have no picture type, the front cover goes first
+        let mut pictures: Vec<&Picture> = self.pictures.iter().collect();
+        pictures.sort_by_key(|p| p.picture_type != PictureType::CoverFront);
+        m4a_tag.set_artworks(pictures.iter().map(|p| {
             let fmt = match p.mime_type.as_str() {
                 "image/bmp" => ImgFmt::Bmp,
-                "image/Png" => ImgFmt::Png,
+                "image/png" | "image/Png" => ImgFmt::Png,
                 "image/jpeg" | &_ => ImgFmt::Jpeg,
             };
-
-            let img = Img {
+            Img {
                 data: p.data.clone(),
                 fmt,
-            };
+            }
+        }));
 
-            m4a_tag.set_artwork(img);
+        for key in &REPLAYGAIN_KEYS {
+            m4a_tag.remove_data_of(&mp4ameta::FreeformIdent::new(MP4_FREEFORM_MEAN, key));
         }
+        for (key, value) in self.replaygain.as_tags() {
+            let ident = mp4ameta::FreeformIdent::new(MP4_FREEFORM_MEAN, key);
+            m4a_tag.set_data(ident, mp4ameta::Data::Utf8(value));
+        }
+        self.fields.write_mp4(&mut m4a_tag);
 
         if let Some(file) = self.file() {
             m4a_tag
@@ -266,21 +408,43 @@ impl Song {
                 .as_ref()
                 .unwrap_or(&String::from("Unknown Album"))],
         );
-        flac_tag.remove_vorbis("Lyrics");
+        let lyrics_keys: Vec<String> = flac_tag
+            .vorbis_comments()
+            .map(|v| {
+                v.comments
+                    .keys()
+                    .filter(|k| is_lyrics_key(k))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        for key in lyrics_keys {
+            flac_tag.remove_vorbis(&key);
+        }
+        for (key, text) in lyrics_items(VORBIS_LYRICS, &self.lyric_frames) {
+            flac_tag.set_vorbis(key, vec![text]);
+        }
 
-        if !self.lyric_frames.is_empty() {
-            let lyric_frames = self.lyric_frames.clone();
-            for l in lyric_frames {
-                flac_tag.set_vorbis("Lyrics", vec![l.text]);
+        // flac picture blocks are the same as METADATA_BLOCK_PICTURE of ogg
+        flac_tag.remove_blocks(metaflac::BlockType::Picture);
+        for p in &self.pictures {
+            let bytes = ogg_picture_from_id3(p).as_apic_bytes();
+            if let Ok(picture) = metaflac::block::Picture::from_bytes(&bytes) {
+                flac_tag.push_block(metaflac::Block::Picture(picture));
             }
         }
 
-        if let Some(p) = &self.picture {
-            flac_tag.add_picture(
-                p.mime_type.clone(),
-                metaflac::block::PictureType::Other,
-                p.data.clone(),
-            );
+        for key in &REPLAYGAIN_KEYS {
+            flac_tag.remove_vorbis(key);
+        }
+        for (key, value) in self.replaygain.as_tags() {
+            flac_tag.set_vorbis(key, vec![value]);
+        }
+        for key in &fields::VORBIS_KEYS {
+            flac_tag.remove_vorbis(key);
+        }
+        for (key, value) in self.fields.as_vorbis() {
+            flac_tag.set_vorbis(key, vec![value]);
         }
 
         let file = self.file().ok_or_else(|| anyhow!("no file found"))?;
@@ -300,40 +464,84 @@ impl Song {
         f_in_disk.read_to_end(&mut f_in_ram)?;
 
         let f_in = Cursor::new(&f_in_ram);
+        let mut f_out = replace_comment_header(f_in, &self.vorbis_comments());
+        let mut f_out_disk = File::create(file)?;
+        std::io::copy(&mut f_out, &mut f_out_disk)?;
+        Ok(())
+    }
+
+    // opus uses the same comments as vorbis, in the OpusTags packet
+    fn save_opus_tag(&self) -> Result<()> {
+        let file = self.file().ok_or_else(|| anyhow!("no file found"))?;
+        let f_in_ram = std::fs::read(file)?;
+
+        let f_in = Cursor::new(&f_in_ram);
+        let mut new_comment = self.vorbis_comments();
+        if let Ok(old_comment) = read_opus_comment_header(Cursor::new(&f_in_ram)) {
+            new_comment.set_vendor(&old_comment.get_vendor());
+        }
+        let mut f_out = replace_opus_comment_header(f_in, &new_comment);
+        let mut f_out_disk = File::create(file)?;
+        std::io::copy(&mut f_out, &mut f_out_disk)?;
+        Ok(())
+    }
+
+    fn vorbis_comments(&self) -> CommentHeader {
         let mut new_comment = CommentHeader::new();
         new_comment.set_vendor("Ogg");
         new_comment.add_tag_single("artist", self.artist().unwrap_or("Unknown Artist"));
         new_comment.add_tag_single("title", self.title().unwrap_or("Unknown Artist"));
         new_comment.add_tag_single("album", self.album().unwrap_or("Unknown Artist"));
-        if !self.lyric_frames.is_empty() {
-            let lyric_frames = self.lyric_frames.clone();
-            for l in lyric_frames {
-                new_comment.add_tag_single("lyrics", &l.text);
-            }
+        for (key, text) in lyrics_items(VORBIS_LYRICS, &self.lyric_frames) {
+            new_comment.add_tag_single(&key, &text);
         }
-        if let Some(p) = &self.picture {
-            let mime_type = match p.mime_type.as_str() {
-                "image/bmp" => MimeType::Bmp,
-                "image/Png" => MimeType::Png,
-                "image/jpeg" | &_ => MimeType::Jpeg,
-            };
-            let picture_ogg = ogg_picture::OggPicture::new(
-                OggPictureType::CoverFront,
-                mime_type,
-                Some("some image".to_string()),
-                (0, 0),
-                0,
-                0,
-                p.data.clone(),
-            );
+        for p in &self.pictures {
+            let picture_ogg = ogg_picture_from_id3(p);
             let picture_decoded = ogg_picture::OggPicture::as_apic_bytes(&picture_ogg);
             let picture_encoded = base64::encode(&picture_decoded);
             new_comment.add_tag_single("METADATA_BLOCK_PICTURE", &picture_encoded);
         }
+        for (key, value) in self.replaygain.as_tags() {
+            new_comment.add_tag_single(key, &value);
+        }
+        for (key, value) in self.fields.as_vorbis() {
+            new_comment.add_tag_single(key, &value);
+        }
+        new_comment
+    }
 
-        let mut f_out = replace_comment_header(f_in, &new_comment);
-        let mut f_out_disk = File::create(file)?;
-        std::io::copy(&mut f_out, &mut f_out_disk)?;
+    // save_ape_tag writes the apev2 tag of wavpack and monkey's audio files
+    fn save_ape_tag(&self) -> Result<()> {
+        let file = self.file().ok_or_else(|| anyhow!("no file found"))?;
+        let mut ape_tag = ApeTag::read_from_path(file).unwrap_or_default();
+
+        ape_tag.set_text(APE_ARTIST, self.artist().unwrap_or("Unknown Artist"));
+        ape_tag.set_text(APE_TITLE, self.title().unwrap_or("Unknown Title"));
+        ape_tag.set_text(APE_ALBUM, self.album().unwrap_or("Unknown Album"));
+        ape_tag.retain(|i| !is_lyrics_key(&i.key));
+        for (key, text) in lyrics_items(APE_LYRICS, &self.lyric_frames) {
+            ape_tag.set_text(&key, &text);
+        }
+        ape_tag.retain(|i| !(i.is_binary() && i.key.starts_with("Cover Art")));
+        for p in &self.pictures {
+            let picture_ape = ogg_picture_from_id3(p);
+            ape_tag.set_binary(
+                picture_ape.pic_type.as_ape_key(),
+                picture_ape.as_ape_bytes(),
+            );
+        }
+        ape_tag.retain(|i| !REPLAYGAIN_KEYS.contains(&i.key.to_uppercase().as_str()));
+        for (key, value) in self.replaygain.as_tags() {
+            ape_tag.set_text(key, &value);
+        }
+        ape_tag.retain(|i| !fields::APE_KEYS.contains(&i.key.to_uppercase().as_str()));
+        for (key, value) in self.fields.as_ape() {
+            ape_tag.set_text(key, &value);
+        }
+
+        ape_tag
+            .write_to_path(file)
+            .map_err(|e| anyhow!("write ape tag error {:?}", e))?;
         // if let Some(s) = self.file() {
         //     let mut ogg_tag = if let Ok(tag) = lofty::Tag::new()
         //         .with_tag_type(TagType::Ogg(lofty::OggFormat::Vorbis))
@@ -396,7 +604,10 @@ impl Song {
             "{}-{}.{}",
             self.artist().unwrap_or("Unknown Artist"),
             self.title().unwrap_or("Unknown Title"),
-            self.ext().unwrap_or("mp3"),
+            self.format
+                .map(FileFormat::ext)
+                .or_else(|| self.ext())
+                .unwrap_or("mp3"),
         );
         let new_name_path: &Path = Path::new(new_name.as_str());
         if let Some(file) = self.file() {
@@ -409,6 +620,10 @@ impl Song {
                     .file_name()
                     .and_then(OsStr::to_str)
                     .map(std::string::ToString::to_string);
+                self.ext = p_new
+                    .extension()
+                    .and_then(OsStr::to_str)
+                    .map(std::string::ToString::to_string);
             }
         }
         Ok(())
@@ -438,22 +653,51 @@ impl Song {
         self.lyric_frames = lyric_frames;
     }
 
+    // set_photo adds the picture, or replaces the one of the same type
     pub fn set_photo(&mut self, picture: Picture) {
-        self.picture = Some(picture);
+        match self
+            .pictures
+            .iter_mut()
+            .find(|p| p.picture_type == picture.picture_type)
+        {
+            Some(p) => *p = picture,
+            None => self.pictures.push(picture),
+        }
+    }
+
+    pub fn remove_picture(&mut self, index: usize) {
+        if index < self.pictures.len() {
+            self.pictures.remove(index);
+        }
+    }
+
+    // next_picture_type changes the type of the picture to the next one not in use
+    pub fn next_picture_type(&mut self, index: usize) {
+        let current = match self.pictures.get(index) {
+            Some(p) => u8::from(p.picture_type),
+            None => return,
+        };
+        for n in 1..PICTURE_TYPES.len() {
+            let next = (usize::from(current) + n) % PICTURE_TYPES.len();
+            let picture_type = PICTURE_TYPES[next];
+            if !self.pictures.iter().any(|p| p.picture_type == picture_type) {
+                self.pictures[index].picture_type = picture_type;
+                return;
+            }
+        }
+    }
+
+    /// The picture shown as cover, front cover first
+    pub fn cover(&self) -> Option<&Picture> {
+        self.pictures
+            .iter()
+            .find(|p| p.picture_type == PictureType::CoverFront)
+            .or_else(|| self.pictures.first())
     }
 
     #[allow(clippy::cast_possible_truncation)]
     fn from_mp3(s: &str) -> Self {
-        let p: &Path = Path::new(s);
-        let ext = p.extension().and_then(OsStr::to_str);
-        let name = p
-            .file_name()
-            .and_then(OsStr::to_str)
-            .map(std::string::ToString::to_string);
-
-        let id3_tag = if let Ok(tag) = id3::Tag::read_from_path(s) {
-            tag
-        } else {
+        let id3_tag = sylt::read_id3(s, FileFormat::Mp3).unwrap_or_else(|_| {
             let mut t = id3::Tag::new();
             let p_mp3: &Path = Path::new(s);
             if let Some(p_base) = p_mp3.file_stem() {
@@ -461,7 +705,39 @@ impl Song {
             }
             let _drop = t.write_to_path(p_mp3, id3::Version::Id3v24);
             t
-        };
+        });
+
+        let mut id3_tag_duration = id3_tag.clone();
+        let duration = id3_tag.duration().map_or_else(
+            || {
+                let duration_player = player::duration(s);
+                id3_tag_duration.set_duration(duration_player.as_millis() as u32);
+                let _drop = id3_tag_duration.write_to_path(s, id3::Version::Id3v24);
+                duration_player
+            },
+            |d| Duration::from_millis(d.into()),
+        );
+
+        Self::from_id3(s, &id3_tag, duration)
+    }
+
+    fn from_wav(s: &str) -> Self {
+        let id3_tag = sylt::read_id3(s, FileFormat::Wav).unwrap_or_default();
+        Self::from_id3(s, &id3_tag, player::duration(s))
+    }
+
+    fn from_aiff(s: &str) -> Self {
+        let id3_tag = sylt::read_id3(s, FileFormat::Aiff).unwrap_or_default();
+        Self::from_id3(s, &id3_tag, player::duration(s))
+    }
+
+    fn from_id3(s: &str, id3_tag: &id3::Tag, duration: Duration) -> Self {
+        let p: &Path = Path::new(s);
+        let ext = p.extension().and_then(OsStr::to_str);
+        let name = p
+            .file_name()
+            .and_then(OsStr::to_str)
+            .map(std::string::ToString::to_string);
 
         let artist: Option<String> = id3_tag.artist().map(String::from);
         let album: Option<String> = id3_tag.album().map(String::from);
@@ -470,6 +746,12 @@ impl Song {
         for l in id3_tag.lyrics().cloned() {
             lyrics.push(l);
         }
+        // SYLT frames written by termusic have a USLT copy, others are added
+        for l in sylt::synced_lyrics(id3_tag) {
+            if !lyrics.iter().any(|u| u.description == l.description) {
+                lyrics.push(l);
+            }
+        }
         lyrics.sort_by_cached_key(|a| a.description.clone());
 
         let parsed_lyric = if lyrics.is_empty() {
@@ -480,22 +762,13 @@ impl Song {
                 Err(_) => None,
             }
         };
-        let mut picture: Option<Picture> = None;
-        let mut p_iter = id3_tag.pictures();
-        if let Some(p) = p_iter.next() {
-            picture = Some(p.clone());
-        }
+        let pictures: Vec<Picture> = id3_tag.pictures().cloned().collect();
 
-        let mut id3_tag_duration = id3_tag.clone();
-        let duration = id3_tag.duration().map_or_else(
-            || {
-                let duration_player = GStreamer::duration(s);
-                id3_tag_duration.set_duration((duration_player.mseconds()) as u32);
-                let _drop = id3_tag_duration.write_to_path(s, id3::Version::Id3v24);
-                Duration::from_millis(duration_player.mseconds())
-            },
-            |d| Duration::from_millis(d.into()),
-        );
+        let mut replaygain = ReplayGain::default();
+        for t in id3_tag.extended_texts() {
+            replaygain.set(&t.description, &t.value);
+        }
+        let fields = TagFields::from_id3(id3_tag);
 
         let file = Some(String::from(s));
 
@@ -507,10 +780,13 @@ impl Song {
             duration,
             name,
             ext: ext.map(String::from),
+            format: None,
+            fields,
             lyric_frames: lyrics,
             lyric_selected: 0,
             parsed_lyric,
-            picture,
+            pictures,
+            replaygain,
         }
     }
 
@@ -538,39 +814,54 @@ impl Song {
         let album: Option<String> = m4a_tag.album().map(String::from);
         let title: Option<String> = m4a_tag.title().map(String::from);
 
-        let lyrics = m4a_tag.lyrics().map(String::from);
-        let mut parsed_lyric: Option<Lyric> = None;
-        if let Some(l) = &lyrics {
-            parsed_lyric = match Lyric::from_str(l) {
-                Ok(l) => Some(l),
-                Err(_) => None,
+        let mut lyrics = LyricsReader::default();
+        for text in m4a_tag.strings_of(&mp4ameta::ident::LYRICS) {
+            lyrics.add(VORBIS_LYRICS, text);
+        }
+        for (ident, data) in m4a_tag.data() {
+            if let (mp4ameta::DataIdent::Freeform { mean, name }, Some(text)) =
+                (ident, data.string())
+            {
+                if mean == MP4_FREEFORM_MEAN {
+                    lyrics.add(name, text);
+                }
             }
         }
-
-        let mut lyric_frames: Vec<Lyrics> = Vec::new();
-        if let Some(s) = lyrics {
-            lyric_frames.push(Lyrics {
-                lang: String::from("eng"),
-                description: String::from("Termusic"),
-                text: s,
-            });
-        };
-
-        let mut picture: Option<Picture> = None;
-        if let Some(artwork) = m4a_tag.artwork() {
+        let lyric_frames = lyrics.finish();
+        let parsed_lyric = lyric_frames
+            .first()
+            .and_then(|l| Lyric::from_str(&l.text).ok());
+
+        // mp4 has no picture type, the first artwork is taken as front cover
+        let mut pictures: Vec<Picture> = Vec::new();
+        for (i, artwork) in m4a_tag.artworks().enumerate() {
             let fmt = match artwork.fmt {
                 ImgFmt::Bmp => "image/bmp",
                 ImgFmt::Jpeg => "image/jpeg",
                 ImgFmt::Png => "image/png",
             };
-            picture = Some(Picture {
+            pictures.push(Picture {
                 mime_type: fmt.to_string(),
-                picture_type: PictureType::Other,
+                picture_type: if i == 0 {
+                    PictureType::CoverFront
+                } else {
+                    PictureType::Other
+                },
                 description: "some image".to_string(),
                 data: artwork.data.to_vec(),
             });
         }
 
+        let mut replaygain = ReplayGain::default();
+        for key in &REPLAYGAIN_KEYS {
+            let ident = mp4ameta::FreeformIdent::new(MP4_FREEFORM_MEAN, key);
+            let value = m4a_tag.strings_of(&ident).next().map(ToString::to_string);
+            if let Some(value) = value {
+                replaygain.set(key, &value);
+            }
+        }
+        let fields = TagFields::from_mp4(&m4a_tag);
+
         let duration = m4a_tag.duration().unwrap_or_else(|| Duration::from_secs(0));
 
         let file = Some(String::from(s));
@@ -582,10 +873,13 @@ impl Song {
             duration,
             name,
             ext: ext.map(String::from),
+            format: None,
+            fields,
             lyric_frames,
             lyric_selected: 0,
             parsed_lyric,
-            picture,
+            pictures,
+            replaygain,
         }
     }
 
@@ -639,35 +933,38 @@ impl Song {
             title = Some(title_string);
         }
 
-        let mut lyric_frames: Vec<Lyrics> = vec![];
-        let lyric_vec = flac_tag.get_vorbis("Lyrics");
-        if let Some(l_vec) = lyric_vec {
-            for l in l_vec {
-                lyric_frames.push(Lyrics {
-                    lang: "eng".to_string(),
-                    description: "termusic".to_string(),
-                    text: l.to_string(),
-                });
-            }
-        }
-
-        let mut parsed_lyric: Option<Lyric> = None;
-        if let Some(l) = lyric_frames.get(0) {
-            parsed_lyric = match Lyric::from_str(&l.text) {
-                Ok(l) => Some(l),
-                Err(_) => None,
+        let mut lyrics = LyricsReader::default();
+        if let Some(vorbis) = flac_tag.vorbis_comments() {
+            for (key, values) in &vorbis.comments {
+                for value in values {
+                    lyrics.add(key, value);
+                }
             }
         }
-
-        let mut picture: Option<Picture> = None;
-        let mut picture_iter = flac_tag.pictures();
-        if let Some(p) = picture_iter.next() {
-            picture = Some(Picture {
+        let lyric_frames = lyrics.finish();
+        let parsed_lyric = lyric_frames
+            .first()
+            .and_then(|l| Lyric::from_str(&l.text).ok());
+
+        let pictures: Vec<Picture> = flac_tag
+            .pictures()
+            .map(|p| Picture {
                 mime_type: p.mime_type.clone(),
-                picture_type: PictureType::Other,
-                description: "some image".to_string(),
+                picture_type: picture_type_from_u32(p.picture_type as u32),
+                description: p.description.clone(),
                 data: p.data.clone(),
-            });
+            })
+            .collect();
+
+        let mut replaygain = ReplayGain::default();
+        let mut fields = TagFields::default();
+        if let Some(vorbis) = flac_tag.vorbis_comments() {
+            for (key, values) in &vorbis.comments {
+                if let Some(value) = values.first() {
+                    replaygain.set(key, value);
+                    fields.set_vorbis(key, value);
+                }
+            }
         }
 
         let mut duration = Duration::from_secs(0);
@@ -688,13 +985,34 @@ impl Song {
             duration,
             name,
             ext: ext.map(String::from),
+            format: None,
+            fields,
             lyric_frames,
             lyric_selected: 0,
             parsed_lyric,
-            picture,
+            pictures,
+            replaygain,
         }
     }
     fn from_ogg(s: &str) -> Self {
+        let comment_list = File::open(s)
+            .ok()
+            .and_then(|f| lewton::inside_ogg::OggStreamReader::new(f).ok())
+            .map(|song| song.comment_hdr.comment_list)
+            .unwrap_or_default();
+        Self::from_vorbis_comments(s, comment_list)
+    }
+
+    fn from_opus(s: &str) -> Self {
+        let comment_list = File::open(s)
+            .ok()
+            .and_then(|f| read_opus_comment_header(f).ok())
+            .map(|header| header.comment_list)
+            .unwrap_or_default();
+        Self::from_vorbis_comments(s, comment_list)
+    }
+
+    fn from_vorbis_comments(s: &str, comment_list: Vec<(String, String)>) -> Self {
         let p: &Path = Path::new(s);
         let ext = p.extension().and_then(OsStr::to_str);
 
@@ -707,65 +1025,43 @@ impl Song {
         let mut title = "Unknown Title".to_string();
         let mut album = " ".to_string();
         let mut artist = "Unknown Artist".to_string();
-        let mut lyrics_text = "".to_string();
-        let mut picture_encoded = "".to_string();
+        let mut lyrics = LyricsReader::default();
+        let mut pictures_encoded: Vec<String> = Vec::new();
+        let mut replaygain = ReplayGain::default();
+        let mut fields = TagFields::default();
 
         //get the title, album, and artist of the song
-        if let Ok(song_file) = File::open(s) {
-            if let Ok(song) = lewton::inside_ogg::OggStreamReader::new(song_file) {
-                for comment in song.comment_hdr.comment_list {
-                    match comment.0.as_str() {
-                        "TITLE" | "title" => title = comment.1,
-                        "ALBUM" | "album" => album = comment.1,
-                        "ARTIST" | "artist" => artist = comment.1,
-                        "LYRICS" | "lyrics" => lyrics_text = comment.1,
-                        "METADATA_BLOCK_PICTURE" | "metadata_block_picture" => {
-                            picture_encoded = comment.1;
-                        }
-                        _ => {}
-                    }
-                }
-            }
-        }
-        let mut picture: Option<Picture> = None;
-        if let Ok(picture_decoded) = base64::decode(picture_encoded) {
-            if let Ok(p) = ogg_picture::OggPicture::from_apic_bytes(&picture_decoded) {
-                let mime_type = match p.mime_type {
-                    MimeType::Jpeg => "image/jpeg".to_string(),
-                    MimeType::Png => "image/png".to_string(),
-                    MimeType::Bmp => "image/bmp".to_string(),
-                    MimeType::Gif => "image/gif".to_string(),
-                    MimeType::Tiff => "image/tiff".to_string(),
-                };
-                let p_id3 = Picture {
-                    mime_type,
-                    picture_type: PictureType::CoverFront,
-                    description: "some image".to_string(),
-                    data: p.data.to_vec(),
-                };
-                picture = Some(p_id3);
+        for comment in comment_list {
+            if lyrics.add(&comment.0, &comment.1) {
+                continue;
             }
-        }
-
-        let mut lyric_frames: Vec<Lyrics> = Vec::new();
-        let mut parsed_lyric: Option<Lyric> = None;
-        if lyrics_text.len() > 10 {
-            let lyrics = Lyrics {
-                lang: "eng".to_string(),
-                description: "termusic".to_string(),
-                text: lyrics_text,
-            };
-            lyric_frames = vec![lyrics];
-            if let Some(l) = lyric_frames.get(0) {
-                parsed_lyric = match Lyric::from_str(&l.text) {
-                    Ok(l) => Some(l),
-                    Err(_) => None,
+            match comment.0.as_str() {
+                "TITLE" | "title" => title = comment.1,
+                "ALBUM" | "album" => album = comment.1,
+                "ARTIST" | "artist" => artist = comment.1,
+                "METADATA_BLOCK_PICTURE" | "metadata_block_picture" => {
+                    pictures_encoded.push(comment.1);
+                }
+                _ => {
+                    replaygain.set(&comment.0, &comment.1);
+                    fields.set_vorbis(&comment.0, &comment.1);
                 }
             }
         }
+        let pictures: Vec<Picture> = pictures_encoded
+            .iter()
+            .filter_map(|p| base64::decode(p).ok())
+            .filter_map(|p| ogg_picture::OggPicture::from_apic_bytes(&p).ok())
+            .map(|p| id3_picture_from_ogg(&p))
+            .collect();
+
+        let lyric_frames = lyrics.finish();
+        let parsed_lyric = lyric_frames
+            .first()
+            .and_then(|l| Lyric::from_str(&l.text).ok());
 
         //get the song duration
-        let duration = GStreamer::duration(s).into();
+        let duration = player::duration(s);
         // let mut duration = Duration::from_secs(0);
         // if let Ok(song_file2) = File::open(s) {
         //     if let Ok(mut song_meta_vec) = ogg_metadata::read_format(song_file2) {
@@ -852,10 +1148,89 @@ impl Song {
             duration,
             name,
             ext: ext.map(String::from),
+            format: None,
+            fields,
             lyric_frames,
             lyric_selected: 0,
             parsed_lyric,
-            picture,
+            pictures,
+            replaygain,
+        }
+    }
+
+    // from_ape_tagged reads wavpack and monkey's audio files, both use apev2 tags
+    fn from_ape_tagged(s: &str) -> Self {
+        let p: &Path = Path::new(s);
+        let ext = p.extension().and_then(OsStr::to_str);
+        let name = p
+            .file_name()
+            .and_then(OsStr::to_str)
+            .map(std::string::ToString::to_string);
+
+        let ape_tag = ApeTag::read_from_path(s).unwrap_or_default();
+        let title = ape_tag
+            .text(APE_TITLE)
+            .or_else(|| p.file_stem().map(|t| t.to_string_lossy().to_string()));
+
+        let mut lyrics = LyricsReader::default();
+        for i in ape_tag.items().filter(|i| !i.is_binary()) {
+            lyrics.add(&i.key, &String::from_utf8_lossy(&i.value));
+        }
+        let lyric_frames = lyrics.finish();
+        let parsed_lyric = lyric_frames
+            .first()
+            .and_then(|l| Lyric::from_str(&l.text).ok());
+
+        let pictures: Vec<Picture> = ape_tag
+            .items()
+            .filter(|i| i.is_binary() && i.key.starts_with("Cover Art"))
+            .filter_map(|i| OggPicture::from_ape_bytes(&i.key, &i.value).ok())
+            .map(|p| id3_picture_from_ogg(&p))
+            .collect();
+
+        let mut replaygain = ReplayGain::default();
+        let mut fields = TagFields::default();
+        for i in ape_tag.items().filter(|i| !i.is_binary()) {
+            let value = String::from_utf8_lossy(&i.value);
+            replaygain.set(&i.key, &value);
+            fields.set_ape(&i.key, &value);
+        }
+
+        Self {
+            artist: ape_tag.text(APE_ARTIST),
+            album: ape_tag.text(APE_ALBUM),
+            title,
+            file: Some(String::from(s)),
+            duration: player::duration(s),
+            name,
+            ext: ext.map(String::from),
+            format: None,
+            fields,
+            lyric_frames,
+            lyric_selected: 0,
+            parsed_lyric,
+            pictures,
+            replaygain,
+        }
+    }
+
+    /// Build a queue item of a radio station, the url is kept as file
+    pub fn from_station(name: &str, url: &str) -> Self {
+        Self {
+            artist: Some(String::from("Radio")),
+            album: Some(String::from(url)),
+            title: Some(String::from(name)),
+            file: Some(String::from(url)),
+            duration: Duration::from_secs(0),
+            name: Some(String::from(name)),
+            ext: None,
+            format: None,
+            fields: TagFields::default(),
+            lyric_frames: Vec::new(),
+            lyric_selected: 0,
+            parsed_lyric: None,
+            pictures: Vec::new(),
+            replaygain: ReplayGain::default(),
         }
     }
 }
@@ -865,15 +1240,24 @@ impl FromStr for Song {
     // type Err = std::string::ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if player::is_stream(s) {
+            return Ok(Self::from_station(s, s));
+        }
         let p: &Path = Path::new(s);
         let ext = p.extension().and_then(OsStr::to_str);
 
-        match ext {
-            Some("mp3") => Ok(Self::from_mp3(s)),
-            Some("m4a") => Ok(Self::from_m4a(s)),
-            Some("flac") => Ok(Self::from_flac(s)),
-            Some("ogg") => Ok(Self::from_ogg(s)),
-            _ => {
+        // the content decides the reader, as the extension could be wrong or missing
+        let format = FileFormat::detect(s);
+        let mut song = match format {
+            Some(FileFormat::Mp3) => Self::from_mp3(s),
+            Some(FileFormat::M4a) => Self::from_m4a(s),
+            Some(FileFormat::Flac) => Self::from_flac(s),
+            Some(FileFormat::Ogg) => Self::from_ogg(s),
+            Some(FileFormat::Opus) => Self::from_opus(s),
+            Some(FileFormat::Wav) => Self::from_wav(s),
+            Some(FileFormat::Aiff) => Self::from_aiff(s),
+            Some(FileFormat::WavPack | FileFormat::Ape) => Self::from_ape_tagged(s),
+            None => {
                 let artist = Some(String::from("Not Support?"));
                 let album = Some(String::from("Not Support?"));
                 let title = Some(String::from(s));
@@ -882,8 +1266,8 @@ impl FromStr for Song {
                 let name = Some(String::from(""));
                 let parsed_lyric: Option<Lyric> = None;
                 let lyric_frames: Vec<Lyrics> = Vec::new();
-                let picture: Option<Picture> = None;
-                Ok(Self {
+                let pictures: Vec<Picture> = Vec::new();
+                Self {
                     artist,
                     album,
                     title,
@@ -891,12 +1275,209 @@ impl FromStr for Song {
                     duration,
                     name,
                     ext: ext.map(String::from),
+                    format: None,
+                    fields: TagFields::default(),
                     lyric_frames,
                     lyric_selected: 0,
                     parsed_lyric,
-                    picture,
-                })
+                    pictures,
+                    replaygain: ReplayGain::default(),
+                }
             }
+        };
+        song.format = format;
+        Ok(song)
+    }
+}
+
+fn id3_picture_from_ogg(p: &OggPicture) -> Picture {
+    let mime_type = match p.mime_type {
+        MimeType::Jpeg => "image/jpeg".to_string(),
+        MimeType::Png => "image/png".to_string(),
+        MimeType::Bmp => "image/bmp".to_string(),
+        MimeType::Gif => "image/gif".to_string(),
+        MimeType::Tiff => "image/tiff".to_string(),
+    };
+    Picture {
+        mime_type,
+        picture_type: picture_type_from_u32(p.pic_type.as_u32()),
+        description: p
+            .description
+            .as_ref()
+            .map_or_else(|| "some image".to_string(), ToString::to_string),
+        data: p.data.to_vec(),
+    }
+}
+
+fn ogg_picture_from_id3(p: &Picture) -> OggPicture {
+    let mime_type = match p.mime_type.as_str() {
+        "image/bmp" => MimeType::Bmp,
+        "image/png" | "image/Png" => MimeType::Png,
+        "image/gif" => MimeType::Gif,
+        "image/tiff" => MimeType::Tiff,
+        "image/jpeg" | &_ => MimeType::Jpeg,
+    };
+    OggPicture::new(
+        OggPictureType::from_u32(u32::from(u8::from(p.picture_type))),
+        mime_type,
+        Some(p.description.clone()),
+        (0, 0),
+        0,
+        0,
+        p.data.clone(),
+    )
+}
+
+// PICTURE_TYPES are ordered by their number in apic frames and flac picture blocks
+const PICTURE_TYPES: [PictureType; 21] = [
+    PictureType::Other,
+    PictureType::Icon,
+    PictureType::OtherIcon,
+    PictureType::CoverFront,
+    PictureType::CoverBack,
+    PictureType::Leaflet,
+    PictureType::Media,
+    PictureType::LeadArtist,
+    PictureType::Artist,
+    PictureType::Conductor,
+    PictureType::Band,
+    PictureType::Composer,
+    PictureType::Lyricist,
+    PictureType::RecordingLocation,
+    PictureType::DuringRecording,
+    PictureType::DuringPerformance,
+    PictureType::ScreenCapture,
+    PictureType::BrightFish,
+    PictureType::Illustration,
+    PictureType::BandLogo,
+    PictureType::PublisherLogo,
+];
+
+fn picture_type_from_u32(n: u32) -> PictureType {
+    usize::try_from(n)
+        .ok()
+        .and_then(|n| PICTURE_TYPES.get(n))
+        .copied()
+        .unwrap_or(PictureType::Other)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    // picture builds a jpeg picture, data is kept after the jpeg magic so it can be told apart
+    fn picture(picture_type: PictureType, data: u8) -> Picture {
+        Picture {
+            mime_type: "image/jpeg".to_string(),
+            picture_type,
+            description: String::new(),
+            data: vec![0xFF, 0xD8, 0xFF, 0xE0, data],
         }
     }
+
+    // temp_file writes content to a file in a new temp directory, and returns both paths
+    fn temp_file(name: &str, content: &[u8]) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!(
+            "termusic-song-{}-{}",
+            name.replace('.', "-"),
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join(name);
+        std::fs::write(&file, content).unwrap();
+        (dir, file)
+    }
+
+    #[test]
+    fn test_pictures() {
+        let (dir, file) = temp_file("song.wv", b"wvpk some audio data");
+        let mut song = Song::from_str(&file.to_string_lossy()).unwrap();
+        assert_eq!(song.format, Some(FileFormat::WavPack));
+        assert!(song.pictures.is_empty());
+
+        song.set_photo(picture(PictureType::Artist, 0));
+        song.set_photo(picture(PictureType::CoverFront, 1));
+        song.set_photo(picture(PictureType::CoverFront, 2));
+        assert_eq!(song.pictures.len(), 2);
+        assert_eq!(song.cover().map(|p| p.data[4]), Some(2));
+
+        // CoverBack is free again after the removal, so the front cover moves to it
+        song.set_photo(picture(PictureType::CoverBack, 3));
+        song.remove_picture(2);
+        song.next_picture_type(1);
+        assert_eq!(song.pictures[1].picture_type, PictureType::CoverBack);
+        assert_eq!(song.cover().map(|p| p.data[4]), Some(0));
+
+        // every typed picture survives writing and reading the tag
+        song.set_photo(picture(PictureType::CoverFront, 4));
+        song.write_tag().unwrap();
+        let song = Song::from_str(&file.to_string_lossy()).unwrap();
+        let mut pictures: Vec<(PictureType, u8)> = song
+            .pictures
+            .iter()
+            .map(|p| (p.picture_type, p.data[4]))
+            .collect();
+        pictures.sort_by_key(|(_, data)| *data);
+        assert_eq!(
+            pictures,
+            vec![
+                (PictureType::Artist, 0),
+                (PictureType::CoverBack, 2),
+                (PictureType::CoverFront, 4),
+            ]
+        );
+        assert_eq!(song.cover().map(|p| p.data[4]), Some(4));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // flac builds the marker and an empty last streaminfo block of a flac file
+    fn flac() -> Vec<u8> {
+        let mut content = b"fLaC\x80\x00\x00\x22".to_vec();
+        content.resize(content.len() + 34, 0);
+        content
+    }
+
+    #[test]
+    fn test_fix_extension() {
+        let (dir, file) = temp_file("song.mp3", &flac());
+        let target = dir.join("song.flac");
+        std::fs::write(&target, b"other").unwrap();
+
+        let mut song = Song::from_str(&file.to_string_lossy()).unwrap();
+        assert_eq!(song.format_mismatch(), Some(FileFormat::Flac));
+        // the other file is kept
+        assert!(song.fix_extension().is_err());
+        assert_eq!(std::fs::read(&target).unwrap(), b"other");
+        assert!(file.exists());
+
+        std::fs::remove_file(&target).unwrap();
+        song.fix_extension().unwrap();
+        assert_eq!(song.file(), Some(target.to_string_lossy().as_ref()));
+        assert_eq!(song.format_mismatch(), None);
+        assert!(!file.exists());
+        assert_eq!(std::fs::read(&target).unwrap(), flac());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_fix_extension_uppercase() {
+        let (dir, file) = temp_file("song.Flac", &flac());
+        let song = Song::from_str(&file.to_string_lossy()).unwrap();
+        assert_eq!(song.format_mismatch(), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let (dir, file) = temp_file("song.MP3", &flac());
+        let mut song = Song::from_str(&file.to_string_lossy()).unwrap();
+        assert_eq!(song.format_mismatch(), Some(FileFormat::Flac));
+        song.fix_extension().unwrap();
+        assert_eq!(
+            song.file(),
+            Some(dir.join("song.flac").to_string_lossy().as_ref())
+        );
+        assert_eq!(song.format_mismatch(), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -1,7 +1,8 @@
-// Read and write vorbiscomment metadata
+// Read and write vorbiscomment metadata, of both vorbis and opus streams
 
 //use lewton::header::CommentHeader;
-use anyhow::Result;
+use anyhow::{bail, Result};
+use byteorder::{LittleEndian, ReadBytesExt};
 use ogg::writing::PacketWriteEndInfo;
 use ogg::{Packet, PacketReader, PacketWriter};
 use std::convert::TryInto;
@@ -9,6 +10,9 @@ use std::io::{Cursor, Read, Seek};
 
 pub type CommentHeader = lewton::header::CommentHeader;
 
+// magic signature of the opus comment packet, it comes right after OpusHead
+const OPUS_TAGS: &[u8; 8] = b"OpusTags";
+
 //type VorbisComments = CommentHeader;
 #[allow(clippy::new_ret_no_self)]
 pub trait VorbisComments {
@@ -99,10 +103,6 @@ pub fn make_comment_header(header: &CommentHeader) -> Vec<u8> {
     //Signature
     let start = [3_u8, 118, 111, 114, 98, 105, 115];
 
-    //Vendor number of bytes as u32
-    let vendor = header.vendor.as_bytes();
-    let vendor_len: u32 = vendor.len().try_into().unwrap_or_default();
-
     //end byte
     let end: u8 = 1;
 
@@ -110,6 +110,25 @@ pub fn make_comment_header(header: &CommentHeader) -> Vec<u8> {
 
     //write start
     new_packet.extend(start.iter().copied());
+    new_packet.extend(make_comment_list(header));
+    new_packet.push(end);
+    new_packet
+}
+
+// make_opus_comment_header is the same as vorbis, except the signature and no framing bit
+pub fn make_opus_comment_header(header: &CommentHeader) -> Vec<u8> {
+    let mut new_packet: Vec<u8> = OPUS_TAGS.to_vec();
+    new_packet.extend(make_comment_list(header));
+    new_packet
+}
+
+// make_comment_list writes vendor and comments shared by vorbis and opus headers
+fn make_comment_list(header: &CommentHeader) -> Vec<u8> {
+    //Vendor number of bytes as u32
+    let vendor = header.vendor.as_bytes();
+    let vendor_len: u32 = vendor.len().try_into().unwrap_or_default();
+
+    let mut new_packet: Vec<u8> = vec![];
 
     //write vendor
     new_packet.extend(vendor_len.to_le_bytes().iter().copied());
@@ -141,7 +160,6 @@ pub fn make_comment_header(header: &CommentHeader) -> Vec<u8> {
                 .copied(),
         );
     }
-    new_packet.push(end);
     //println!("{:?}",new_packet);
     new_packet
 }
@@ -164,13 +182,69 @@ pub fn read_comment_header<T: Read + Seek>(f_in: T) -> Result<CommentHeader> {
     // comment_hdr
 }
 
+// read_opus_comment_header looks for OpusTags in the first packets of the stream
+pub fn read_opus_comment_header<T: Read + Seek>(f_in: T) -> Result<CommentHeader> {
+    let mut reader = PacketReader::new(f_in);
+    // OpusHead and OpusTags are the first two packets, unless there are other streams
+    for _ in 0..8 {
+        let packet: Packet = reader.read_packet_expected()?;
+        if packet.data.starts_with(OPUS_TAGS) {
+            return read_comment_list(&packet.data[OPUS_TAGS.len()..]);
+        }
+    }
+    bail!("OpusTags not found")
+}
+
+fn read_comment_list(data: &[u8]) -> Result<CommentHeader> {
+    let mut cursor = Cursor::new(data);
+    let vendor = read_string(&mut cursor)?;
+    let count = cursor.read_u32::<LittleEndian>()?;
+    let mut comment_list = Vec::new();
+    for _ in 0..count {
+        let comment = read_string(&mut cursor)?;
+        if let Some((key, value)) = comment.split_once('=') {
+            comment_list.push((key.to_string(), value.to_string()));
+        }
+    }
+    Ok(CommentHeader {
+        vendor,
+        comment_list,
+    })
+}
+
+fn read_string(cursor: &mut Cursor<&[u8]>) -> Result<String> {
+    let len = cursor.read_u32::<LittleEndian>()?;
+    let mut buf = vec![0; len.try_into()?];
+    cursor.read_exact(&mut buf)?;
+    Ok(String::from_utf8_lossy(&buf).to_string())
+}
+
 #[allow(unused)]
 pub fn replace_comment_header<T: Read + Seek>(
     f_in: T,
     new_header: &CommentHeader,
 ) -> Cursor<Vec<u8>> {
-    let new_comment_data = make_comment_header(new_header);
+    replace_header_packet(f_in, &make_comment_header(new_header), |data| {
+        lewton::header::read_header_comment(data).is_ok()
+    })
+}
 
+pub fn replace_opus_comment_header<T: Read + Seek>(
+    f_in: T,
+    new_header: &CommentHeader,
+) -> Cursor<Vec<u8>> {
+    replace_header_packet(f_in, &make_opus_comment_header(new_header), |data| {
+        data.starts_with(OPUS_TAGS)
+    })
+}
+
+// replace_header_packet copies the stream, with the first packet matched by is_header
+// replaced by new_comment_data
+fn replace_header_packet<T: Read + Seek>(
+    f_in: T,
+    new_comment_data: &[u8],
+    is_header: fn(&[u8]) -> bool,
+) -> Cursor<Vec<u8>> {
     let f_out_ram: Vec<u8> = vec![];
     let mut f_out = Cursor::new(f_out_ram);
 
@@ -191,16 +265,10 @@ pub fn replace_comment_header<T: Read + Seek>(
                         } else {
                             PacketWriteEndInfo::NormalPacket
                         };
-                        if !header_done {
-                            let comment_hdr = lewton::header::read_header_comment(&packet.data);
-                            match comment_hdr {
-                                Ok(_hdr) => {
-                                    // This is the packet to replace
-                                    packet.data = new_comment_data.clone();
-                                    header_done = true;
-                                }
-                                Err(_error) => {}
-                            }
+                        if !header_done && is_header(&packet.data) {
+                            // This is the packet to replace
+                            packet.data = new_comment_data.to_vec();
+                            header_done = true;
                         }
                         let lastpacket = packet.last_in_stream() && packet.last_in_page();
                         let stream_serial = packet.stream_serial();
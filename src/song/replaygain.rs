@@ -0,0 +1,333 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+// ReplayGain values are stored as text in all formats:
+// ID3 TXXX frames, vorbis comments(flac and ogg) and mp4 freeform atoms
+// under com.apple.iTunes. The keys are the same everywhere.
+// REPLAYGAIN_TRACK_GAIN=-6.54 dB
+// REPLAYGAIN_TRACK_PEAK=0.988553
+pub const REPLAYGAIN_TRACK_GAIN: &str = "REPLAYGAIN_TRACK_GAIN";
+pub const REPLAYGAIN_TRACK_PEAK: &str = "REPLAYGAIN_TRACK_PEAK";
+pub const REPLAYGAIN_ALBUM_GAIN: &str = "REPLAYGAIN_ALBUM_GAIN";
+pub const REPLAYGAIN_ALBUM_PEAK: &str = "REPLAYGAIN_ALBUM_PEAK";
+pub const REPLAYGAIN_KEYS: [&str; 4] = [
+    REPLAYGAIN_TRACK_GAIN,
+    REPLAYGAIN_TRACK_PEAK,
+    REPLAYGAIN_ALBUM_GAIN,
+    REPLAYGAIN_ALBUM_PEAK,
+];
+// mean of the mp4 freeform atoms
+pub const MP4_FREEFORM_MEAN: &str = "com.apple.iTunes";
+
+#[cfg(feature = "gst")]
+use anyhow::anyhow;
+use anyhow::{bail, Result};
+#[cfg(feature = "gst")]
+use gst::ClockTime;
+#[cfg(feature = "gst")]
+use gstreamer as gst;
+#[cfg(feature = "gst")]
+use gstreamer::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplayGainMode {
+    #[default]
+    Off,
+    Track,
+    Album,
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct ReplayGain {
+    /// Track gain in dB
+    pub track_gain: Option<f64>,
+    /// Track peak, 1.0 means full scale
+    pub track_peak: Option<f64>,
+    /// Album gain in dB
+    pub album_gain: Option<f64>,
+    /// Album peak, 1.0 means full scale
+    pub album_peak: Option<f64>,
+}
+
+impl ReplayGain {
+    pub const fn is_empty(&self) -> bool {
+        self.track_gain.is_none()
+            && self.track_peak.is_none()
+            && self.album_gain.is_none()
+            && self.album_peak.is_none()
+    }
+
+    // as_tags formats the values the way other taggers do, gain with 2 decimals and peak
+    // with 6. None values are skipped.
+    pub fn as_tags(&self) -> Vec<(&'static str, String)> {
+        let mut tags = vec![];
+        if let Some(g) = self.track_gain {
+            tags.push((REPLAYGAIN_TRACK_GAIN, format!("{:.2} dB", g)));
+        }
+        if let Some(p) = self.track_peak {
+            tags.push((REPLAYGAIN_TRACK_PEAK, format!("{:.6}", p)));
+        }
+        if let Some(g) = self.album_gain {
+            tags.push((REPLAYGAIN_ALBUM_GAIN, format!("{:.2} dB", g)));
+        }
+        if let Some(p) = self.album_peak {
+            tags.push((REPLAYGAIN_ALBUM_PEAK, format!("{:.6}", p)));
+        }
+        tags
+    }
+
+    // set parses one tag, key is case insensitive. Unknown keys are ignored.
+    pub fn set(&mut self, key: &str, value: &str) {
+        let value = parse_value(value);
+        match key.to_uppercase().as_str() {
+            REPLAYGAIN_TRACK_GAIN => self.track_gain = value,
+            REPLAYGAIN_TRACK_PEAK => self.track_peak = value,
+            REPLAYGAIN_ALBUM_GAIN => self.album_gain = value,
+            REPLAYGAIN_ALBUM_PEAK => self.album_peak = value,
+            _ => {}
+        }
+    }
+
+    // volume_factor returns the linear factor to multiply the volume with. The gain of the
+    // chosen mode is used first, then the other one, then fallback for untagged songs.
+    // preamp and fallback are in dB. The factor is limited by peak so it won't clip.
+    pub fn volume_factor(&self, mode: ReplayGainMode, preamp: f64, fallback: f64) -> f64 {
+        let (gain, peak) = match mode {
+            ReplayGainMode::Off => return 1.0,
+            ReplayGainMode::Track => (
+                self.track_gain.or(self.album_gain),
+                self.track_peak.or(self.album_peak),
+            ),
+            ReplayGainMode::Album => (
+                self.album_gain.or(self.track_gain),
+                self.album_peak.or(self.track_peak),
+            ),
+        };
+        let gain = gain.map_or(fallback, |g| g + preamp);
+        let factor = 10_f64.powf(gain / 20.0);
+        match peak {
+            Some(peak) if peak > 0.0 => factor.min(1.0 / peak),
+            _ => factor,
+        }
+    }
+}
+
+// TrackLoudness is the analysis result of one file
+#[derive(Clone, Copy, Debug)]
+pub struct TrackLoudness {
+    pub gain: f64,
+    pub peak: f64,
+    // duration in seconds, used to weight the track in album gain
+    pub duration: f64,
+}
+
+// ReplayGain 2.0 target, gain is the difference between it and the integrated loudness
+#[cfg(feature = "gst")]
+const REFERENCE_LOUDNESS: f64 = -18.0;
+
+// analyze_track decodes the whole file, ebur128level(gst-plugins-bad) measures the integrated
+// loudness in LUFS, and rganalysis(gst-plugins-good) the sample peak.
+#[cfg(feature = "gst")]
+#[allow(clippy::cast_precision_loss)]
+pub fn analyze_track(file: &str) -> Result<TrackLoudness> {
+    let pipeline = gst::parse_launch(
+        "filesrc name=src ! decodebin ! audioconvert ! audioresample ! rganalysis ! \
+         ebur128level post-messages=true ! fakesink",
+    )?
+    .downcast::<gst::Pipeline>()
+    .map_err(|_| anyhow!("failed to create analysis pipeline"))?;
+    let src = pipeline
+        .by_name("src")
+        .ok_or_else(|| anyhow!("failed to find filesrc"))?;
+    src.set_property("location", file)?;
+    let bus = pipeline
+        .bus()
+        .ok_or_else(|| anyhow!("pipeline without bus"))?;
+
+    pipeline.set_state(gst::State::Playing)?;
+    let mut loudness = None;
+    let mut peak = None;
+    let mut error = None;
+    while let Some(msg) = bus.timed_pop_filtered(
+        ClockTime::NONE,
+        &[
+            gst::MessageType::Element,
+            gst::MessageType::Tag,
+            gst::MessageType::Eos,
+            gst::MessageType::Error,
+        ],
+    ) {
+        match msg.view() {
+            // posted every second, the last one covers the whole track
+            gst::MessageView::Element(e) => {
+                if let Some(s) = e.structure().filter(|s| s.name() == "ebur128-level") {
+                    if let Ok(l) = s.get::<f64>("global-loudness") {
+                        loudness = Some(l);
+                    }
+                }
+            }
+            gst::MessageView::Tag(t) => {
+                if let Some(p) = t.tags().get::<gst::tags::TrackPeak>() {
+                    peak = Some(p.get());
+                }
+            }
+            gst::MessageView::Error(e) => {
+                error = Some(e.error().to_string());
+                break;
+            }
+            _ => break,
+        }
+    }
+    let duration = pipeline
+        .query_duration::<ClockTime>()
+        .map_or(0.0, |d| d.mseconds() as f64 / 1000.0);
+    pipeline.set_state(gst::State::Null)?;
+
+    if let Some(e) = error {
+        bail!("analyze {} error: {}", file, e);
+    }
+    match (loudness, peak) {
+        (Some(loudness), Some(peak)) if loudness.is_finite() => Ok(TrackLoudness {
+            gain: REFERENCE_LOUDNESS - loudness,
+            peak,
+            duration,
+        }),
+        _ => bail!("no replaygain result for {}", file),
+    }
+}
+
+// analysis needs gstreamer, other backends don't scan files
+#[cfg(not(feature = "gst"))]
+pub fn analyze_track(file: &str) -> Result<TrackLoudness> {
+    bail!(
+        "analyze {} error: replaygain scan needs the gst feature",
+        file
+    )
+}
+
+// album_gain combines the loudness of all tracks, weighted by duration, and returns the album
+// gain and peak.
+#[allow(clippy::cast_precision_loss)]
+pub fn album_gain(tracks: &[TrackLoudness]) -> (f64, f64) {
+    let total: f64 = tracks.iter().map(|t| t.duration.max(1.0)).sum();
+    let energy: f64 = tracks
+        .iter()
+        .map(|t| t.duration.max(1.0) * 10_f64.powf(-t.gain / 10.0))
+        .sum();
+    let gain = if total > 0.0 {
+        -10.0 * (energy / total).log10()
+    } else {
+        0.0
+    };
+    let peak = tracks.iter().map(|t| t.peak).fold(0.0, f64::max);
+    (gain, peak)
+}
+
+// "-6.54 dB", "+1.20dB" and "0.988553" are all valid
+fn parse_value(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let value = value
+        .strip_suffix("dB")
+        .or_else(|| value.strip_suffix("db"))
+        .or_else(|| value.strip_suffix("DB"))
+        .unwrap_or(value);
+    value.trim().trim_start_matches('+').parse::<f64>().ok()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{album_gain, ReplayGain, ReplayGainMode, TrackLoudness};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_replaygain_set() {
+        let mut rg = ReplayGain::default();
+        rg.set("REPLAYGAIN_TRACK_GAIN", "-6.54 dB");
+        rg.set("replaygain_track_peak", "0.988553");
+        rg.set("REPLAYGAIN_ALBUM_GAIN", "+1.20dB");
+        rg.set("REPLAYGAIN_ALBUM_PEAK", "not a number");
+        assert_eq!(rg.track_gain, Some(-6.54));
+        assert_eq!(rg.track_peak, Some(0.988_553));
+        assert_eq!(rg.album_gain, Some(1.2));
+        assert_eq!(rg.album_peak, None);
+    }
+
+    #[test]
+    fn test_replaygain_volume_factor() {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        let mut rg = ReplayGain::default();
+        assert!(close(
+            rg.volume_factor(ReplayGainMode::Track, 0.0, -6.0),
+            10_f64.powf(-0.3)
+        ));
+        rg.set("REPLAYGAIN_TRACK_GAIN", "-20 dB");
+        assert!(close(rg.volume_factor(ReplayGainMode::Off, 0.0, 0.0), 1.0));
+        assert!(close(
+            rg.volume_factor(ReplayGainMode::Track, 0.0, 0.0),
+            0.1
+        ));
+        // album mode falls back to track gain
+        assert!(close(
+            rg.volume_factor(ReplayGainMode::Album, 0.0, 0.0),
+            0.1
+        ));
+        rg.set("REPLAYGAIN_TRACK_GAIN", "+20 dB");
+        rg.set("REPLAYGAIN_TRACK_PEAK", "0.5");
+        assert!(close(
+            rg.volume_factor(ReplayGainMode::Track, 0.0, 0.0),
+            2.0
+        ));
+    }
+
+    #[test]
+    fn test_replaygain_album_gain() {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        let track = |gain, peak, duration| TrackLoudness {
+            gain,
+            peak,
+            duration,
+        };
+        let (gain, peak) = album_gain(&[track(-6.0, 0.5, 100.0), track(-6.0, 0.9, 200.0)]);
+        assert!(close(gain, -6.0));
+        assert!(close(peak, 0.9));
+        // the louder track dominates
+        let (gain, _) = album_gain(&[track(-10.0, 1.0, 100.0), track(0.0, 1.0, 100.0)]);
+        assert!(gain < -5.0 && gain > -10.0);
+
+        let rg = ReplayGain {
+            track_gain: Some(-6.541),
+            album_peak: Some(0.5),
+            ..ReplayGain::default()
+        };
+        assert_eq!(
+            rg.as_tags(),
+            vec![
+                ("REPLAYGAIN_TRACK_GAIN", "-6.54 dB".to_string()),
+                ("REPLAYGAIN_ALBUM_PEAK", "0.500000".to_string())
+            ]
+        );
+    }
+}
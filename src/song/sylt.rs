@@ -0,0 +1,309 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+// The id3 crate writes SYLT frames in a way other taggers can't read, and fails on the ones
+// they write. So SYLT frames are taken out before the tag is parsed, and kept as raw frames.
+use super::FileFormat;
+use crate::songtag::lrc::Lyric;
+use anyhow::{bail, Result};
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use id3::frame::{Content, Frame, Lyrics};
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::str::FromStr;
+
+const SYLT: &str = "SYLT";
+const ID3_HEADER_LEN: usize = 10;
+// text encodings of id3 frames
+const LATIN1: u8 = 0;
+const UTF16: u8 = 1;
+const UTF16BE: u8 = 2;
+const UTF8: u8 = 3;
+// time stamps in milliseconds, the other format counts mpeg frames
+const FORMAT_MS: u8 = 2;
+const CONTENT_LYRICS: u8 = 1;
+
+/// Read the id3 tag of mp3 files, or the id3 chunk of wav and aiff files. SYLT frames are
+/// kept as unknown frames, see `synced_lyrics` to decode them.
+pub fn read_id3(path: &str, format: FileFormat) -> Result<id3::Tag> {
+    let mut file = File::open(path)?;
+    match format {
+        FileFormat::Wav => seek_id3_chunk(&mut file, false)?,
+        FileFormat::Aiff => seek_id3_chunk(&mut file, true)?,
+        _ => {}
+    }
+    let (data, frames) = split_sylt(read_tag_bytes(&mut file)?);
+    let mut tag =
+        id3::Tag::read_from(Cursor::new(data)).or_else(|mut e| e.partial_tag.take().ok_or(e))?;
+    for frame in frames {
+        tag.add_frame(Frame::with_content(SYLT, Content::Unknown(frame)));
+    }
+    Ok(tag)
+}
+
+/// Decode the SYLT frames of tag as lyrics in lrc format
+pub fn synced_lyrics(tag: &id3::Tag) -> Vec<Lyrics> {
+    tag.frames()
+        .filter(|f| f.id() == SYLT)
+        .filter_map(|f| match f.content() {
+            Content::Unknown(data) => decode_sylt(data),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Build a SYLT frame of lyrics in lrc format, None if the lyrics have no time stamps
+pub fn sylt_frame(lyrics: &Lyrics) -> Option<Frame> {
+    let captions = Lyric::from_str(&lyrics.text).ok()?.captions();
+    if captions.is_empty() {
+        return None;
+    }
+    let mut data = vec![UTF8];
+    data.extend(lyrics.lang.bytes().chain(std::iter::repeat(b' ')).take(3));
+    data.extend_from_slice(&[FORMAT_MS, CONTENT_LYRICS]);
+    data.extend_from_slice(lyrics.description.as_bytes());
+    data.push(0);
+    for (time_stamp, text) in captions {
+        data.extend_from_slice(text.as_bytes());
+        data.push(0);
+        data.extend_from_slice(&u32::try_from(time_stamp).unwrap_or(u32::MAX).to_be_bytes());
+    }
+    Some(Frame::with_content(SYLT, Content::Unknown(data)))
+}
+
+// seek_id3_chunk moves to the id3 chunk of riff (wav) or iff (aiff) files
+fn seek_id3_chunk(file: &mut File, big_endian: bool) -> Result<()> {
+    file.seek(SeekFrom::Start(12))?;
+    let mut chunk = [0; 8];
+    loop {
+        file.read_exact(&mut chunk)?;
+        if chunk[..4].eq_ignore_ascii_case(b"id3 ") {
+            return Ok(());
+        }
+        let size = if big_endian {
+            BigEndian::read_u32(&chunk[4..])
+        } else {
+            LittleEndian::read_u32(&chunk[4..])
+        };
+        // chunks are padded to even size
+        file.seek(SeekFrom::Current(i64::from(size) + i64::from(size % 2)))?;
+    }
+}
+
+// read_tag_bytes reads the id3v2 tag at current position, header included
+fn read_tag_bytes(file: &mut File) -> Result<Vec<u8>> {
+    let mut data = vec![0; ID3_HEADER_LEN];
+    file.read_exact(&mut data)?;
+    if &data[..3] != b"ID3" {
+        bail!("no id3 tag found");
+    }
+    let size = syncsafe(&data[6..10]);
+    data.resize(ID3_HEADER_LEN + size, 0);
+    file.read_exact(&mut data[ID3_HEADER_LEN..])?;
+    Ok(data)
+}
+
+// split_sylt takes the SYLT frames out of id3v2.3 and v2.4 tags, their bodies are returned
+fn split_sylt(data: Vec<u8>) -> (Vec<u8>, Vec<Vec<u8>>) {
+    let version = data[3];
+    let flags = data[5];
+    // v2.2 tags and tags unsynchronised as a whole are left to the id3 crate
+    if !(version == 3 || version == 4) || flags & 0x80 != 0 {
+        return (data, vec![]);
+    }
+    let size_of = |bytes: &[u8]| {
+        if version == 4 {
+            syncsafe(bytes)
+        } else {
+            usize::try_from(BigEndian::read_u32(bytes)).unwrap_or(usize::MAX)
+        }
+    };
+
+    let mut pos = ID3_HEADER_LEN;
+    if flags & 0x40 != 0 && data.len() >= ID3_HEADER_LEN + 4 {
+        // extended header, its size of v2.3 doesn't count the size bytes
+        let size = size_of(&data[10..14]);
+        pos += if version == 4 {
+            size
+        } else {
+            size.saturating_add(4)
+        };
+    }
+    let mut tag = data[..pos.min(data.len())].to_vec();
+    let mut frames = vec![];
+    // frame ids start with capital letters or digits, padding is zero
+    while pos + ID3_HEADER_LEN <= data.len() && data[pos] != 0 {
+        let size = size_of(&data[pos + 4..pos + 8]);
+        let end = (pos + ID3_HEADER_LEN).saturating_add(size).min(data.len());
+        if &data[pos..pos + 4] == SYLT.as_bytes() {
+            // compressed, encrypted and unsynchronised frames are dropped
+            if data[pos + 9] == 0 {
+                frames.push(data[pos + ID3_HEADER_LEN..end].to_vec());
+            }
+        } else {
+            tag.extend_from_slice(&data[pos..end]);
+        }
+        pos = end;
+    }
+
+    // padding and footer are not kept
+    let size = tag.len() - ID3_HEADER_LEN;
+    tag[5] &= !0x10;
+    tag[6..10].copy_from_slice(&to_syncsafe(size));
+    (tag, frames)
+}
+
+fn syncsafe(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .fold(0, |size, b| (size << 7) | usize::from(b & 0x7f))
+}
+
+#[allow(clippy::cast_possible_truncation)]
+const fn to_syncsafe(size: usize) -> [u8; 4] {
+    [
+        (size >> 21 & 0x7f) as u8,
+        (size >> 14 & 0x7f) as u8,
+        (size >> 7 & 0x7f) as u8,
+        (size & 0x7f) as u8,
+    ]
+}
+
+fn decode_sylt(data: &[u8]) -> Option<Lyrics> {
+    if data.len() < 6 || data[4] != FORMAT_MS {
+        return None;
+    }
+    let encoding = data[0];
+    let lang = String::from_utf8_lossy(&data[1..4]).to_string();
+    let (description, mut rest) = read_text(&data[6..], encoding)?;
+
+    let mut syllables: Vec<(u64, String)> = vec![];
+    while let Some((text, next)) = read_text(rest, encoding) {
+        if next.len() < 4 {
+            break;
+        }
+        syllables.push((u64::from(BigEndian::read_u32(next)), text));
+        rest = &next[4..];
+    }
+    if syllables.is_empty() {
+        return None;
+    }
+
+    Some(Lyrics {
+        lang,
+        description,
+        text: Lyric::from_captions(join_syllables(syllables)).as_lrc_text(),
+    })
+}
+
+// join_syllables puts syllables together as lines, a new line starts with a line break
+fn join_syllables(syllables: Vec<(u64, String)>) -> Vec<(u64, String)> {
+    let line_break = |text: &str| text.starts_with(&['\n', '\r'][..]);
+    if !syllables.iter().any(|(_, text)| line_break(text)) {
+        return syllables;
+    }
+    let mut lines: Vec<(u64, String)> = vec![];
+    for (time_stamp, text) in syllables {
+        match lines.last_mut() {
+            Some(line) if !line_break(&text) => line.1.push_str(&text),
+            _ => lines.push((time_stamp, text.trim_start().to_string())),
+        }
+    }
+    lines
+}
+
+// read_text reads a text terminated by zero, and returns the bytes after it
+fn read_text(data: &[u8], encoding: u8) -> Option<(String, &[u8])> {
+    match encoding {
+        UTF16 | UTF16BE => {
+            let end = (0..data.len() / 2)
+                .map(|i| i * 2)
+                .find(|&i| data[i] == 0 && data[i + 1] == 0)?;
+            let (big_endian, bytes) = match &data[..end] {
+                [0xff, 0xfe, bytes @ ..] => (false, bytes),
+                [0xfe, 0xff, bytes @ ..] | bytes => (true, bytes),
+            };
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|c| {
+                    if big_endian {
+                        BigEndian::read_u16(c)
+                    } else {
+                        LittleEndian::read_u16(c)
+                    }
+                })
+                .collect();
+            Some((String::from_utf16_lossy(&units), &data[end + 2..]))
+        }
+        _ => {
+            let end = data.iter().position(|&b| b == 0)?;
+            let text = if encoding == LATIN1 {
+                data[..end].iter().map(|&b| char::from(b)).collect()
+            } else {
+                String::from_utf8_lossy(&data[..end]).to_string()
+            };
+            Some((text, &data[end + 1..]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_sylt_round_trip() {
+        let lyrics = Lyrics {
+            lang: "eng".to_string(),
+            description: "netease".to_string(),
+            text: "[00:01.50]first line\n[00:05.00]second line\n".to_string(),
+        };
+        let mut tag = id3::Tag::new();
+        tag.set_title("song");
+        tag.add_frame(sylt_frame(&lyrics).unwrap());
+        let mut data = vec![];
+        tag.write_to(&mut data, id3::Version::Id3v24).unwrap();
+
+        let (data, frames) = split_sylt(data);
+        assert_eq!(frames.len(), 1);
+        let tag = id3::Tag::read_from(Cursor::new(data)).unwrap();
+        assert_eq!(tag.title(), Some("song"));
+        let decoded = decode_sylt(&frames[0]).unwrap();
+        assert_eq!(decoded.description, lyrics.description);
+        assert_eq!(decoded.text, lyrics.text);
+
+        // syllables of other taggers in utf-16, a line break starts a new line
+        let mut data = vec![UTF16, b'e', b'n', b'g', FORMAT_MS, CONTENT_LYRICS, 0, 0];
+        for (text, time_stamp) in [("ab", 1000_u32), ("cd", 1500), ("\nef", 3000)] {
+            data.extend_from_slice(&[0xff, 0xfe]);
+            data.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+            data.extend_from_slice(&[0, 0]);
+            data.extend_from_slice(&time_stamp.to_be_bytes());
+        }
+        let lyrics = decode_sylt(&data).unwrap();
+        assert_eq!(lyrics.text, "[00:01.00]abcd\n[00:03.00]ef\n");
+    }
+}
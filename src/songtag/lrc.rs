@@ -26,6 +26,7 @@
 // [al:''Album where the song is from'']
 // [ar:''Lyrics artist'']
 // [by:''Creator of the LRC file'']
+// [length:''How long the song is'']
 // [offset:''+/- Overall timestamp adjustment in milliseconds, + shifts time up, - shifts down'']
 // [re:''The player or editor that creates LRC file'']
 // [ti:''Lyrics (song) title'']
@@ -36,114 +37,136 @@
 // [al:Hits Of The 60's - Vol. 2 – Oldies]
 // [00:12.00]Lyrics beginning ...
 // [00:15.30]Some more lyrics ...
-use anyhow::Result;
+// A line repeated in the song may have several time stamps:
+// [00:20.00][01:30.00]Chorus ...
+// Time stamps are [mm:ss], [mm:ss.xx], [mm:ss.xxx], [mm:ss:xx] or [hh:mm:ss.xx].
+// Enhanced lrc adds word timings inside the line:
+// [00:12.00]<00:12.00>Lyrics <00:12.50>beginning <00:13.20>...
+use anyhow::{anyhow, bail, Result};
 use lazy_static::lazy_static;
 use regex::Regex;
-use std::cmp::Ordering;
 use std::str::FromStr;
-use std::time::Duration;
 
 lazy_static! {
-    static ref LYRICS_RE: Regex = Regex::new("^[^\x00-\x08\x0A-\x1F\x7F]*$").unwrap();
-    static ref TAG_RE: Regex = Regex::new(r"\[.*:.*\]").unwrap();
-    static ref LINE_STARTS_WITH_RE: Regex =
-        Regex::new("^\\[([^\x00-\x08\x0A-\x1F\x7F\\[\\]:]*):([^\x00-\x08\x0A-\x1F\x7F\\[\\]]*)\\]")
-            .unwrap();
+    static ref WORD_TIME_RE: Regex = Regex::new(r"<([0-9:.]+)>").unwrap();
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Lyric {
     pub offset: i64, // positive means delay lyric
     pub lang_extension: Option<String>,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub by: Option<String>,
+    // length of the song in milliseconds
+    pub length: Option<u64>,
     pub unsynced_captions: Vec<UnsyncedCaption>, // USLT captions
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct UnsyncedCaption {
     time_stamp: u64,
     text: String,
+    // start time and text of each word, empty for plain lrc
+    words: Vec<(u64, String)>,
 }
 
 const EOL: &str = "\n";
+// max difference of time stamps in milliseconds between a line and its translation
+const TRANSLATION_TOLERANCE: u64 = 1000;
 
 impl Lyric {
-    // GetText will fetch lyric by time in seconds
-    pub fn get_text(&self, mut time: u64) -> Option<String> {
-        if self.unsynced_captions.is_empty() {
-            return None;
-        };
-
-        // here we want to show lyric 2 second earlier
+    // lyric_time converts playing time in milliseconds to time of lyric, with offset applied
+    const fn lyric_time(&self, time_ms: u64) -> u64 {
         if self.offset > 0 {
-            time = time * 1000 + 2000 + self.offset.abs() as u64;
+            time_ms + self.offset.unsigned_abs()
         } else {
-            time = time * 1000 + 2000 - self.offset.abs() as u64;
+            time_ms.saturating_sub(self.offset.unsigned_abs())
         }
-
-        let mut text = self.unsynced_captions.get(0)?.text.clone();
-        for v in &self.unsynced_captions {
-            if time >= v.time_stamp {
-                text = v.text.clone();
-            } else {
-                break;
-            }
-        }
-        Some(text)
     }
 
-    pub fn get_index(&self, mut time: u64) -> Option<usize> {
+    // get_index returns the index of line to show at time in milliseconds
+    pub fn get_index(&self, time_ms: u64) -> Option<usize> {
         if self.unsynced_captions.is_empty() {
             return None;
         };
 
-        // here we want to show lyric 1 second earlier
-        if self.offset >= 0 {
-            time = time * 1000 + 2000 + self.offset.abs() as u64;
+        let index_at = |time: u64| {
+            let time = self.lyric_time(time);
+            self.unsynced_captions
+                .iter()
+                .take_while(|v| time >= v.time_stamp)
+                .count()
+                .saturating_sub(1)
+        };
+        // here we want to show lyric 2 seconds earlier, unless words of the line are still sung
+        let index = index_at(time_ms);
+        if self.unsynced_captions[index].words.is_empty() {
+            Some(index_at(time_ms + 2000))
         } else {
-            time = time * 1000 + 2000 - self.offset.abs() as u64;
+            Some(index)
         }
+    }
 
-        let mut index: usize = 0;
-        for (i, v) in self.unsynced_captions.iter().enumerate() {
-            if time >= v.time_stamp {
-                index = i;
-            } else {
-                break;
-            }
+    // get_words splits the line at time in milliseconds into the sung and unsung words. It
+    // returns None for lines without word timings, which are shown as a whole
+    pub fn get_words(&self, time_ms: u64) -> Option<(String, String)> {
+        let caption = self.unsynced_captions.get(self.get_index(time_ms)?)?;
+        if caption.words.is_empty() {
+            return None;
         }
-        Some(index)
+        let time = self.lyric_time(time_ms);
+        let sung = caption.words.iter().take_while(|(t, _)| *t <= time).count();
+        let (sung, unsung) = caption.words.split_at(sung);
+        let join = |words: &[(u64, String)]| words.iter().map(|(_, w)| w.as_str()).collect();
+        Some((join(sung), join(unsung)))
     }
 
     pub fn adjust_offset(&mut self, time: u64, offset: i64) {
-        if let Some(index) = self.get_index(time) {
+        if let Some(index) = self.get_index(time * 1000) {
             // when time stamp is less than 10 seconds or index is before the first line, we adjust
             // the offset.
             if (index == 0) | (time < 11) {
                 self.offset -= offset;
             } else {
-                // fine tuning each line after 10 seconds
-                let mut v = &mut self.unsynced_captions[index];
-                let adjusted_time_stamp = if offset > 0 {
-                    v.time_stamp + offset.abs() as u64
-                } else {
-                    v.time_stamp - offset.abs() as u64
-                };
-                v.time_stamp = match adjusted_time_stamp.cmp(&0) {
-                    Ordering::Greater | Ordering::Equal => adjusted_time_stamp as u64,
-                    Ordering::Less => 0,
+                // fine tuning each line after 10 seconds, words move together with the line
+                let v = &mut self.unsynced_captions[index];
+                let shift = |t: u64| {
+                    if offset > 0 {
+                        t + offset.unsigned_abs()
+                    } else {
+                        t.saturating_sub(offset.unsigned_abs())
+                    }
                 };
+                v.time_stamp = shift(v.time_stamp);
+                for (t, _) in &mut v.words {
+                    *t = shift(*t);
+                }
             }
         };
         // we sort the captions by time_stamp. This is to fix some lyrics downloaded are not sorted
-        self.unsynced_captions
-            .sort_by(|b, a| b.time_stamp.cmp(&a.time_stamp));
+        self.unsynced_captions.sort_by_key(|c| c.time_stamp);
     }
 
     pub fn as_lrc_text(&self) -> String {
         let mut result: String = String::new();
+        let tags = [
+            ("ti", &self.title),
+            ("ar", &self.artist),
+            ("al", &self.album),
+            ("by", &self.by),
+        ];
+        for (key, value) in tags {
+            if let Some(value) = value {
+                result += &format!("[{}:{}]{}", key, value, EOL);
+            }
+        }
+        if let Some(length) = self.length {
+            result += &format!("[length:{}]{}", time_lrc(length), EOL);
+        }
         if self.offset != 0 {
-            let string_offset = format!("[offset:{}]\n", self.offset);
-            result += string_offset.as_ref();
+            result += &format!("[offset:{}]{}", self.offset, EOL);
         }
 
         for line in &self.unsynced_captions {
@@ -152,133 +175,389 @@ impl Lyric {
         result
     }
 
-    pub fn merge_adjacent(&mut self) {
-        let mut unsynced_captions = self.unsynced_captions.clone();
-        let mut offset = 1;
-        for (i, v) in self.unsynced_captions.iter().enumerate() {
-            if i < 1 {
-                continue;
-            }
-            if let Some(item) = unsynced_captions.get(i - offset) {
-                if v.time_stamp - item.time_stamp < 2000 {
-                    unsynced_captions[i - offset].text += "  ";
-                    unsynced_captions[i - offset].text += v.text.as_ref();
-                    unsynced_captions.remove(i - offset + 1);
-                    offset += 1;
+    // captions returns time stamp in milliseconds and text of each line, with offset applied
+    pub fn captions(&self) -> Vec<(u64, String)> {
+        self.unsynced_captions
+            .iter()
+            .map(|c| {
+                let time_stamp = if self.offset > 0 {
+                    c.time_stamp.saturating_sub(self.offset.unsigned_abs())
+                } else {
+                    c.time_stamp + self.offset.unsigned_abs()
+                };
+                (time_stamp, c.text.clone())
+            })
+            .collect()
+    }
+
+    // translations aligns the lines of translation by time stamp. It returns the translated
+    // text of each line, or None if no line of translation is close enough.
+    pub fn translations(&self, translation: &Self) -> Vec<Option<String>> {
+        let translated = translation.captions();
+        self.captions()
+            .iter()
+            .map(|(time_stamp, _)| {
+                translated
+                    .iter()
+                    .filter(|(_, text)| !text.trim().is_empty())
+                    .min_by_key(|(t, _)| t.abs_diff(*time_stamp))
+                    .filter(|(t, _)| t.abs_diff(*time_stamp) <= TRANSLATION_TOLERANCE)
+                    .map(|(_, text)| text.clone())
+            })
+            .collect()
+    }
+
+    // from_captions builds lyric from lines of synchronized lyrics, like id3 SYLT frames
+    pub fn from_captions(captions: Vec<(u64, String)>) -> Self {
+        let mut unsynced_captions: Vec<UnsyncedCaption> = captions
+            .into_iter()
+            .map(|(time_stamp, text)| UnsyncedCaption {
+                time_stamp,
+                text,
+                words: vec![],
+            })
+            .collect();
+        unsynced_captions.sort_by_key(|c| c.time_stamp);
+        Self {
+            unsynced_captions,
+            ..Self::default()
+        }
+    }
+
+    // parse_tag reads a metadata tag like [ar:artist], unknown tags are ignored
+    fn parse_tag(&mut self, key: &str, value: &str) {
+        let value = value.trim();
+        let text = || (!value.is_empty()).then(|| value.to_string());
+        match key.trim().to_lowercase().as_str() {
+            "ti" => self.title = text(),
+            "ar" => self.artist = text(),
+            "al" => self.album = text(),
+            "by" => self.by = text(),
+            "length" => self.length = parse_time(value).ok(),
+            "offset" => {
+                if let Ok(o) = value.replace(' ', "").parse() {
+                    self.offset = o;
                 }
             }
+            _ => {}
         }
-
-        self.unsynced_captions = unsynced_captions;
     }
 }
 
 impl UnsyncedCaption {
-    fn parse_line(line: &mut String) -> Result<Self, ()> {
-        //[00:12.00]Line 1 lyrics
-        // !line.starts_with('[') | !line.contains(']')
-        // First, parse the time
-        let time_stamp = Self::parse_time(
-            line.get(line.find('[').ok_or(())? + 1..line.find(']').ok_or(())?)
-                .ok_or(())?,
-        )?;
-        let text = line
-            .drain(line.find(']').ok_or(())? + 1..)
-            .collect::<String>();
-        Ok(Self { time_stamp, text })
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    // parse_line reads a line with one or more time stamps. Word timings are relative to the
+    // first time stamp, so they are moved for the repeated lines.
+    fn parse_line(time_stamps: &[u64], text: &str) -> Vec<Self> {
+        let first = time_stamps.iter().min().copied().unwrap_or_default();
+        let words = Self::parse_words(first, text);
+        let text: String = if words.is_empty() {
+            text.to_string()
+        } else {
+            words.iter().map(|(_, w)| w.as_str()).collect()
+        };
+        time_stamps
+            .iter()
+            .map(|&time_stamp| Self {
+                time_stamp,
+                text: text.clone(),
+                words: words
+                    .iter()
+                    .map(|(t, w)| (t + time_stamp - first, w.clone()))
+                    .collect(),
+            })
+            .collect()
     }
 
-    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-    fn parse_time(string: &str) -> Result<u64, ()> {
-        //mm:ss.xx or mm:ss.xxx
-        if !(string.contains(':')) | !(string.contains('.')) {
-            return Err(());
+    // parse_words reads the <mm:ss.xx> word timings of enhanced lrc. Text before the first
+    // timing starts with the line.
+    fn parse_words(time_stamp: u64, text: &str) -> Vec<(u64, String)> {
+        let mut words = vec![];
+        let mut start = time_stamp;
+        let mut last = 0;
+        for cap in WORD_TIME_RE.captures_iter(text) {
+            let (Some(m), Ok(time)) = (cap.get(0), parse_time(&cap[1])) else {
+                continue;
+            };
+            let word = &text[last..m.start()];
+            if !(words.is_empty() && word.is_empty()) {
+                words.push((start, word.to_string()));
+            }
+            start = time;
+            last = m.end();
         }
-        let (x, y) = (string.find(':').ok_or(())?, string.find('.').ok_or(())?);
-        let minute = string.get(0..x).ok_or(())?.parse::<u32>().map_err(|_| ())?;
-        let second = string
-            .get(x + 1..y)
-            .ok_or(())?
-            .parse::<u32>()
-            .map_err(|_| ())?;
-        let micros = &format!("0.{}", string.get(y + 1..).ok_or(())?)
-            .parse::<f64>()
-            .map_err(|_| ())?;
-        let sum_milis =
-            u64::from(minute) * 60 * 1000 + u64::from(second) * 1000 + (micros * 1000.0) as u64;
-        Ok(sum_milis)
+        if last > 0 {
+            // the last timing may have no word, it marks the end of the line
+            words.push((start, text[last..].to_string()));
+        }
+        words
     }
 
     fn as_lrc(&self) -> String {
-        let line = format!("[{}]{}", time_lrc(self.time_stamp), self.text);
+        let mut line = format!("[{}]", time_lrc(self.time_stamp));
+        if self.words.is_empty() {
+            line += &self.text;
+        }
+        for (time_stamp, word) in &self.words {
+            line += &format!("<{}>{}", time_lrc(*time_stamp), word);
+        }
         line + EOL
     }
 }
 
+// parse_time reads mm:ss, mm:ss.xx, mm:ss.xxx, mm:ss:xx or hh:mm:ss.xx into milliseconds
+fn parse_time(string: &str) -> Result<u64> {
+    let number = |s: &str| -> Result<u64> {
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            bail!("invalid time: {}", string);
+        }
+        Ok(s.parse()?)
+    };
+    // fraction of second, like 5 for 500 milliseconds or 05 for 50
+    // digits after the third are below a millisecond, they're dropped before parsing
+    let fraction = |s: &str| -> Result<u64> {
+        if !s.bytes().all(|b| b.is_ascii_digit()) {
+            bail!("invalid time: {}", string);
+        }
+        let digits = number(s.get(..3).unwrap_or(s))?;
+        Ok(match s.len() {
+            1 => digits * 100,
+            2 => digits * 10,
+            _ => digits,
+        })
+    };
+
+    let (clock, millis) = match string.trim().split_once('.') {
+        Some((clock, frac)) => (clock, Some(fraction(frac)?)),
+        None => (string.trim(), None),
+    };
+    let parts: Vec<&str> = clock.split(':').collect();
+    let (hours, minutes, seconds, millis) = match (parts.as_slice(), millis) {
+        ([m, s], millis) => (0, number(m)?, number(s)?, millis.unwrap_or_default()),
+        // without a dot, the third number is the fraction, as in [mm:ss:xx]
+        ([m, s, frac], None) => (0, number(m)?, number(s)?, fraction(frac)?),
+        ([h, m, s], Some(millis)) => (number(h)?, number(m)?, number(s)?, millis),
+        _ => bail!("invalid time: {}", string),
+    };
+    // time stamps come from downloaded lyrics too, huge numbers must not overflow
+    hours
+        .checked_mul(60)
+        .and_then(|t| t.checked_add(minutes))
+        .and_then(|t| t.checked_mul(60))
+        .and_then(|t| t.checked_add(seconds))
+        .and_then(|t| t.checked_mul(1000))
+        .and_then(|t| t.checked_add(millis))
+        .ok_or_else(|| anyhow!("time out of range: {}", string))
+}
+
+// time_lrc formats milliseconds as mm:ss.xx, or mm:ss.xxx if it's not a multiple of 10 ms, so
+// the time survives a round trip. Minutes go beyond 59 instead of adding hours, as most players
+// don't read hours.
 fn time_lrc(time_stamp: u64) -> String {
-    let time_duration = Duration::from_millis(time_stamp);
-    let _h = time_duration.as_secs() / 3600;
-    let m = (time_duration.as_secs() / 60) % 60;
-    let s = time_duration.as_secs() % 60;
-    let ms = time_duration.as_millis() % 60;
-
-    let res = format!("{:02}:{:02}.{:02}", m, s, ms);
-    res
+    let m = time_stamp / 60_000;
+    let s = time_stamp / 1000 % 60;
+    let ms = time_stamp % 1000;
+    if ms.is_multiple_of(10) {
+        format!("{:02}:{:02}.{:02}", m, s, ms / 10)
+    } else {
+        format!("{:02}:{:02}.{:03}", m, s, ms)
+    }
+}
+
+// split_tag returns the content of the tag at start of line, and the rest of line
+fn split_tag(line: &str) -> Option<(&str, &str)> {
+    let line = line.strip_prefix('[')?;
+    let end = line.find(']')?;
+    Some((&line[..end], &line[end + 1..]))
 }
 
 impl FromStr for Lyric {
-    // type Err = std::string::ParseError;
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // s = cleanLRC(s)
-        // lines := strings.Split(s, "\n")
-        let mut offset: i64 = 0;
-        let lang_extension = Some(String::new());
-        let mut unsynced_captions = vec![];
-        for line in s.split('\n') {
-            let mut line = line.to_string();
-            if line.ends_with('\n') {
-                line.pop();
-                if line.ends_with('\r') {
-                    line.pop();
+        let mut lyric = Self {
+            lang_extension: Some(String::new()),
+            ..Self::default()
+        };
+        for line in s.trim_start_matches('\u{feff}').lines() {
+            let mut rest = line.trim();
+            let mut time_stamps = vec![];
+            while let Some((tag, after)) = split_tag(rest) {
+                if let Ok(time_stamp) = parse_time(tag) {
+                    time_stamps.push(time_stamp);
+                } else if let (true, Some((key, value))) =
+                    (time_stamps.is_empty(), tag.split_once(':'))
+                {
+                    lyric.parse_tag(key, value);
+                } else {
+                    // brackets in the text, like [Chorus]
+                    break;
                 }
+                rest = after;
             }
-            let line = line.trim();
-            let mut line = line.to_string();
-            if line.is_empty() {
-                continue;
+            if !time_stamps.is_empty() {
+                lyric
+                    .unsynced_captions
+                    .extend(UnsyncedCaption::parse_line(&time_stamps, rest.trim()));
             }
+        }
 
-            if line.starts_with("[offset") {
-                let line = line.trim_start_matches("[offset:");
-                let line = line.trim_end_matches(']');
-                let line = line.replace(" ", "");
-                if let Ok(o) = line.parse() {
-                    offset = o;
-                }
-            }
+        // we sort the captions by Timestamp. This is to fix some lyrics downloaded are not sorted
+        lyric.unsynced_captions.sort_by_key(|c| c.time_stamp);
 
-            if !LINE_STARTS_WITH_RE.is_match(line.as_ref()) {
-                continue;
-            }
+        Ok(lyric)
+    }
+}
 
-            if let Ok(s) = UnsyncedCaption::parse_line(&mut line) {
-                unsynced_captions.push(s);
-            };
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
 
-        // we sort the captions by Timestamp. This is to fix some lyrics downloaded are not sorted
-        unsynced_captions.sort_by(|b, a| b.time_stamp.cmp(&a.time_stamp));
+    // lines returns time stamp and text of each line, without offset
+    fn lines(lyric: &Lyric) -> Vec<(u64, &str)> {
+        lyric
+            .unsynced_captions
+            .iter()
+            .map(|c| (c.time_stamp, c.text()))
+            .collect()
+    }
 
-        let mut lyric = Self {
-            offset,
-            lang_extension,
-            unsynced_captions,
-        };
+    fn parse(lrc: &str) -> Lyric {
+        let lyric = Lyric::from_str(lrc).unwrap();
+        // writing the lyric back keeps everything
+        assert_eq!(Lyric::from_str(&lyric.as_lrc_text()).unwrap(), lyric);
+        lyric
+    }
 
-        lyric.merge_adjacent();
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("01:02").unwrap(), 62_000);
+        assert_eq!(parse_time("01:02.3").unwrap(), 62_300);
+        assert_eq!(parse_time("01:02.34").unwrap(), 62_340);
+        assert_eq!(parse_time("01:02.345").unwrap(), 62_345);
+        assert_eq!(parse_time("01:02:34").unwrap(), 62_340);
+        assert_eq!(parse_time("1:01:02.34").unwrap(), 3_662_340);
+        assert!(parse_time("ti:title").is_err());
+        assert!(parse_time("01:-2.00").is_err());
+        assert!(parse_time("01.02").is_err());
+        assert_eq!(time_lrc(3_662_340), "61:02.34");
+        assert_eq!(time_lrc(62_345), "01:02.345");
+    }
 
-        Ok(lyric)
+    #[test]
+    fn test_metadata() {
+        let lyric = parse(include_str!("../../tests/lrc/metadata_crlf_bom.lrc"));
+        assert_eq!(lyric.title.as_deref(), Some("Amazing Grace"));
+        assert_eq!(lyric.artist.as_deref(), Some("John Newton"));
+        assert_eq!(lyric.album.as_deref(), Some("Olney Hymns"));
+        assert_eq!(lyric.by.as_deref(), Some("termusic"));
+        assert_eq!(lyric.length, Some(185_000));
+        assert_eq!(lyric.offset, 300);
+        assert_eq!(
+            lines(&lyric),
+            vec![
+                (5100, "Amazing grace! How sweet the sound"),
+                (12_450, "That saved a wretch like me!"),
+                (19_800, "I once was lost, but now am found;"),
+                (27_050, "Was blind, but now I see."),
+                (34_000, ""),
+                (40_200, "[Verse 2]"),
+                (41_500, "'Twas grace that taught my heart to fear,"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_netease() {
+        let lyric = parse(include_str!("../../tests/lrc/netease.lrc"));
+        assert_eq!(lyric.by.as_deref(), Some("anonymous"));
+        assert_eq!(
+            lines(&lyric),
+            vec![
+                (0, "作词 : Jane Taylor"),
+                (1000, "作曲 : Traditional"),
+                (12_370, "Twinkle, twinkle, little star,"),
+                (16_912, "How I wonder what you are!"),
+                (21_456, "Up above the world so high,"),
+                (25_999, "Like a diamond in the sky."),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_repeated_lines() {
+        let lyric = parse(include_str!("../../tests/lrc/repeated_chorus.lrc"));
+        let texts: Vec<(u64, &str)> = lines(&lyric);
+        assert_eq!(texts.len(), 10);
+        assert_eq!(texts[0], (3000, "Row, row, row your boat,"));
+        assert_eq!(texts[4], (15_000, "Row, row, row your boat,"));
+        assert_eq!(texts[7], (24_000, "Life is but a dream."));
+        assert_eq!(texts[9], (30_000, "Gently down the stream."));
+    }
+
+    #[test]
+    fn test_time_formats() {
+        let lyric = parse(include_str!("../../tests/lrc/time_formats.lrc"));
+        assert_eq!(
+            lines(&lyric),
+            vec![
+                (5000, "No fraction"),
+                (10_500, "One digit fraction"),
+                (15_250, "Colon before fraction"),
+                (20_123, "Long fraction"),
+                (3_599_990, "Almost one hour"),
+                (3_600_000, "Minutes beyond an hour"),
+                (3_723_450, "With hours"),
+            ]
+        );
+        assert!(parse_time("999999999999999:00.00").is_err());
+    }
+
+    #[test]
+    fn test_enhanced_lrc() {
+        let lyric = parse(include_str!("../../tests/lrc/enhanced.lrc"));
+        assert_eq!(lyric.get_index(0), Some(0));
+        assert_eq!(
+            lyric.unsynced_captions[0].text(),
+            "Frère Jacques, Frère Jacques,"
+        );
+        assert_eq!(
+            lyric.get_words(1600),
+            Some(("Frère Jacques, ".to_string(), "Frère Jacques,".to_string()))
+        );
+        assert_eq!(
+            lyric.get_words(2900),
+            Some(("Frère Jacques, Frère Jacques,".to_string(), String::new()))
+        );
+        // word timings move with the repeated line
+        assert_eq!(
+            lyric.get_words(9600),
+            Some(("Sonnez les ".to_string(), "matines".to_string()))
+        );
+        let plain = parse("[00:10.00]Plain line");
+        assert_eq!(plain.get_words(10_000), None);
+    }
+
+    #[test]
+    fn test_translations() {
+        let original = parse(include_str!("../../tests/lrc/netease.lrc"));
+        let translation = parse(
+            "[offset:100]\n[00:12.470]一闪一闪亮晶晶\n[00:17.500]满天都是小星星\n[00:21.456]\n[00:40.00]挂在天上放光明",
+        );
+        assert_eq!(
+            original.translations(&translation),
+            vec![
+                None,
+                None,
+                Some("一闪一闪亮晶晶".to_string()),
+                Some("满天都是小星星".to_string()),
+                None,
+                None,
+            ]
+        );
     }
 }
@@ -234,7 +234,7 @@ impl SongTag {
 
         Ok(Picture {
             mime_type: "image/jpeg".to_string(),
-            picture_type: PictureType::Other,
+            picture_type: PictureType::CoverFront,
             description: "some image".to_string(),
             data: encoded_image_bytes,
         })
@@ -1,4 +1,4 @@
-use crate::ui::activity::main::{Status, TermusicActivity};
+use crate::ui::activity::main::{LoopMode, Status, TermusicActivity};
 use dbus::arg::{RefArg, Variant};
 use dbus::blocking::Connection;
 use dbus::channel::MatchingReceiver;
@@ -43,6 +43,33 @@ pub enum MediaPlayback {
     Playing { progress: Option<MediaPosition> },
 }
 
+/// The loop status of the player, as defined by mpris.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LoopStatus {
+    None,
+    Track,
+    Playlist,
+}
+
+impl LoopStatus {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "None",
+            Self::Track => "Track",
+            Self::Playlist => "Playlist",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "None" => Some(Self::None),
+            "Track" => Some(Self::Track),
+            "Playlist" => Some(Self::Playlist),
+            _ => None,
+        }
+    }
+}
+
 /// The metadata of a media item.
 #[derive(Clone, PartialEq, Eq, Debug, Default)]
 pub struct MediaMetadata<'a> {
@@ -72,6 +99,8 @@ pub enum MediaControlEvent {
     SetPosition(MediaPosition),
     /// Open the URI in the media player.
     OpenUri(String),
+    /// Set the loop status of the player.
+    SetLoopStatus(LoopStatus),
 
     /// Bring the media player's user interface to the front using any appropriate mechanism available.
     Raise,
@@ -101,6 +130,7 @@ struct MprisData {
     friendly_name: String,
     metadata: OwnedMetadata,
     playback_status: MediaPlayback,
+    loop_status: LoopStatus,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug, Default)]
@@ -138,6 +168,7 @@ impl MediaControls {
             friendly_name: display_name.to_string(),
             metadata: OwnedMetadata::default(),
             playback_status: MediaPlayback::Stopped,
+            loop_status: LoopStatus::Playlist,
         }));
 
         Self {
@@ -186,6 +217,13 @@ impl MediaControls {
         Ok(())
     }
 
+    /// Set the loop status.
+    pub fn set_loop_status(&mut self, loop_status: LoopStatus) {
+        if let Ok(mut data) = self.shared_data.lock() {
+            data.loop_status = loop_status;
+        }
+    }
+
     /// Set the metadata of the currently playing media item.
     pub fn set_metadata(&mut self, metadata: MediaMetadata) {
         if let Ok(mut data) = self.shared_data.lock() {
@@ -258,6 +296,26 @@ fn mpris_run(
             }
         });
 
+        b.property("LoopStatus")
+            .get({
+                let shared_data = shared_data1.clone();
+                move |_, _| {
+                    let data = shared_data.lock().unwrap();
+                    Ok(data.loop_status.as_str().to_string())
+                }
+            })
+            .set({
+                let event_handler = event_handler.clone();
+                move |_, _, value: String| {
+                    if let Some(loop_status) = LoopStatus::from_str(&value) {
+                        (event_handler.lock().unwrap())(MediaControlEvent::SetLoopStatus(
+                            loop_status,
+                        ));
+                    }
+                    Ok(None)
+                }
+            });
+
         b.property("Position").get({
             let shared_data = shared_data1.clone();
             move |_, _| {
@@ -442,6 +500,10 @@ fn mpris_run(
             "PlaybackStatus".to_string(),
             Variant(Box::new(status.to_string())),
         );
+        changed.changed_properties.insert(
+            "LoopStatus".to_string(),
+            Variant(Box::new(data.loop_status.as_str().to_string())),
+        );
 
         c.channel()
             .send(
@@ -527,6 +589,13 @@ pub fn mpris_handler(e: MediaControlEvent, activity: &mut TermusicActivity) {
         // MediaControlEvent::SetPosition(position) => {
         //     let _position = position. / 1000;
         // }
+        MediaControlEvent::SetLoopStatus(loop_status) => {
+            activity.set_loop_mode(match loop_status {
+                LoopStatus::Playlist => LoopMode::RepeatAll,
+                LoopStatus::Track => LoopMode::RepeatOne,
+                LoopStatus::None => LoopMode::Once,
+            });
+        }
         MediaControlEvent::OpenUri(uri) => {
             activity.player.queue_and_play(&uri);
         } // MediaControlEvent::::Metadata(info, tx) => {
@@ -0,0 +1,314 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use super::TermusicActivity;
+use crate::config::get_app_config_path;
+use crate::songtag::lrc::Lyric;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, read_to_string};
+use std::str::FromStr;
+
+// LyricPair holds the descriptions of lyric frames
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct LyricPair {
+    pub original: String,
+    // translation is shown under each line of the original lyric
+    pub translation: Option<String>,
+}
+
+// LyricPairs is saved as lyric_pairs.toml next to session.toml, keyed by file
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default)]
+pub struct LyricPairs {
+    pub pairs: BTreeMap<String, LyricPair>,
+}
+
+// LyricTranslation is the lyric frame shown as translation, with its lines aligned to the
+// lines of current lyric
+pub struct LyricTranslation {
+    pub index: usize,
+    lyric: Lyric,
+    pub lines: Vec<Option<String>>,
+}
+
+impl TermusicActivity {
+    pub fn load_lyric_pairs(&mut self) -> Result<()> {
+        let mut path = get_app_config_path()?;
+        path.push("lyric_pairs.toml");
+        if !path.exists() {
+            return Ok(());
+        }
+
+        self.lyric_pairs = toml::from_str(&read_to_string(path)?)?;
+        Ok(())
+    }
+
+    // apply_lyric_pair selects the lyric and translation saved for current song
+    pub fn apply_lyric_pair(&mut self) {
+        self.lyric_translation = None;
+        let lyric_pairs = &self.lyric_pairs.pairs;
+        let Some(song) = self.current_song.as_mut() else {
+            return;
+        };
+        let Some(pair) = song.file().and_then(|f| lyric_pairs.get(f)) else {
+            return;
+        };
+        let position = |description: &str| {
+            song.lyric_frames
+                .iter()
+                .position(|f| f.description == description)
+        };
+        let original = position(&pair.original);
+        let translation = pair.translation.as_deref().and_then(position);
+
+        if let Some(index) = original {
+            if let Ok(lyric) = Lyric::from_str(&song.lyric_frames[index].text) {
+                song.lyric_selected = index;
+                song.parsed_lyric = Some(lyric);
+            }
+        }
+        if let Some(index) = translation.filter(|i| *i != song.lyric_selected) {
+            self.set_translation(index);
+        }
+    }
+
+    // switch_translation pairs the next lyric with the one showing, the translation is turned
+    // off after the last lyric. The pair is remembered for the song.
+    pub fn switch_translation(&mut self) {
+        let Some(song) = self.current_song.as_ref() else {
+            return;
+        };
+        if song.lyric_frames.len() < 2 {
+            Self::show_message(
+                &self.sender_message,
+                "No translation",
+                "Song has only one lyric",
+            );
+            return;
+        }
+        let next = self.lyric_translation.as_ref().map_or(0, |t| t.index + 1);
+        let next = (next..song.lyric_frames.len()).find(|i| *i != song.lyric_selected);
+        self.lyric_translation = None;
+        if let Some(index) = next {
+            self.set_translation(index);
+        }
+        let text = self
+            .lyric_translation
+            .as_ref()
+            .zip(self.current_song.as_ref())
+            .map_or_else(
+                || "Translation is off".to_string(),
+                |(t, song)| {
+                    format!(
+                        "{} lyric is shown as translation",
+                        song.lyric_frames[t.index].description
+                    )
+                },
+            );
+        self.remember_lyric_pair();
+        Self::show_message(&self.sender_message, "Translation switched", &text);
+    }
+
+    // set_translation parses the lyric frame with index as translation, it's skipped if the
+    // frame is not a lyric
+    fn set_translation(&mut self, index: usize) {
+        let lyric = self
+            .current_song
+            .as_ref()
+            .and_then(|s| s.lyric_frames.get(index))
+            .and_then(|f| Lyric::from_str(&f.text).ok());
+        self.lyric_translation = lyric.map(|lyric| LyricTranslation {
+            index,
+            lyric,
+            lines: vec![],
+        });
+        self.align_translation();
+    }
+
+    // align_translation matches the lines of translation with current lyric again. It's needed
+    // whenever current lyric is switched or its time stamps are adjusted.
+    pub fn align_translation(&mut self) {
+        let original = self
+            .current_song
+            .as_ref()
+            .and_then(|s| s.parsed_lyric.as_ref());
+        if let Some(translation) = self.lyric_translation.as_mut() {
+            translation.lines =
+                original.map_or_else(Vec::new, |l| l.translations(&translation.lyric));
+        }
+    }
+
+    // remember_lyric_pair remembers the lyric and translation showing for current song, they
+    // are saved on exit
+    pub fn remember_lyric_pair(&mut self) {
+        let Some(song) = self.current_song.as_ref() else {
+            return;
+        };
+        let (Some(file), Some(original)) =
+            (song.file(), song.lyric_frames.get(song.lyric_selected))
+        else {
+            return;
+        };
+        let translation = self
+            .lyric_translation
+            .as_ref()
+            .and_then(|t| song.lyric_frames.get(t.index))
+            .map(|f| f.description.clone());
+        self.lyric_pairs.pairs.insert(
+            file.to_string(),
+            LyricPair {
+                original: original.description.clone(),
+                translation,
+            },
+        );
+    }
+
+    pub fn save_lyric_pairs(&self) -> Result<()> {
+        let mut path = get_app_config_path()?;
+        path.push("lyric_pairs.toml");
+        fs::write(path, toml::to_string(&self.lyric_pairs)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::song::Song;
+    use id3::frame::Lyrics;
+    use pretty_assertions::assert_eq;
+
+    // activity builds an activity playing a song with lyrics of the descriptions, the first
+    // one is showing
+    fn activity(descriptions: &[&str]) -> TermusicActivity {
+        let mut song = Song::from_str("/music/song.mp3").unwrap();
+        song.lyric_frames = descriptions
+            .iter()
+            .map(|d| Lyrics {
+                lang: "eng".to_string(),
+                description: (*d).to_string(),
+                text: ["[00:01.00]", d, "\n[00:05.00]", d, " again"].concat(),
+            })
+            .collect();
+        song.parsed_lyric = Lyric::from_str(&song.lyric_frames[0].text).ok();
+        TermusicActivity {
+            current_song: Some(song),
+            ..TermusicActivity::default()
+        }
+    }
+
+    fn translation(activity: &TermusicActivity) -> Option<usize> {
+        activity.lyric_translation.as_ref().map(|t| t.index)
+    }
+
+    fn pair(original: &str, translation: Option<&str>) -> LyricPair {
+        LyricPair {
+            original: original.to_string(),
+            translation: translation.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_switch_translation() {
+        let mut activity = activity(&["en", "zh", "ja"]);
+        activity.switch_translation();
+        assert_eq!(translation(&activity), Some(1));
+        assert_eq!(
+            activity.lyric_translation.as_ref().unwrap().lines,
+            vec![Some("zh".to_string()), Some("zh again".to_string())]
+        );
+        assert_eq!(
+            activity.lyric_pairs.pairs.get("/music/song.mp3"),
+            Some(&pair("en", Some("zh")))
+        );
+
+        // off after the last lyric, then around again
+        activity.switch_translation();
+        assert_eq!(translation(&activity), Some(2));
+        activity.switch_translation();
+        assert_eq!(translation(&activity), None);
+        assert_eq!(
+            activity.lyric_pairs.pairs.get("/music/song.mp3"),
+            Some(&pair("en", None))
+        );
+        activity.switch_translation();
+        assert_eq!(translation(&activity), Some(1));
+
+        // the showing lyric is skipped
+        let song = activity.current_song.as_mut().unwrap();
+        song.lyric_selected = 1;
+        activity.lyric_translation = None;
+        activity.switch_translation();
+        assert_eq!(translation(&activity), Some(0));
+        activity.switch_translation();
+        assert_eq!(translation(&activity), Some(2));
+    }
+
+    #[test]
+    fn test_apply_lyric_pair() {
+        let mut activity = activity(&["en", "zh", "ja"]);
+        activity
+            .lyric_pairs
+            .pairs
+            .insert("/music/song.mp3".to_string(), pair("ja", Some("en")));
+        activity.apply_lyric_pair();
+        let song = activity.current_song.as_ref().unwrap();
+        assert_eq!(song.lyric_selected, 2);
+        assert_eq!(
+            song.parsed_lyric.as_ref().map(Lyric::captions),
+            Some(vec![
+                (1000, "ja".to_string()),
+                (5000, "ja again".to_string())
+            ])
+        );
+        assert_eq!(translation(&activity), Some(0));
+        assert_eq!(
+            activity.lyric_translation.as_ref().unwrap().lines,
+            vec![Some("en".to_string()), Some("en again".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_apply_lyric_pair_fallback() {
+        // lyrics of the saved descriptions are gone, current lyric is kept
+        let mut activity = activity(&["en", "zh"]);
+        activity
+            .lyric_pairs
+            .pairs
+            .insert("/music/song.mp3".to_string(), pair("ja", Some("fr")));
+        activity.apply_lyric_pair();
+        assert_eq!(activity.current_song.as_ref().unwrap().lyric_selected, 0);
+        assert_eq!(translation(&activity), None);
+
+        // the translation is still applied without its original
+        activity
+            .lyric_pairs
+            .pairs
+            .insert("/music/song.mp3".to_string(), pair("ja", Some("zh")));
+        activity.apply_lyric_pair();
+        assert_eq!(activity.current_song.as_ref().unwrap().lyric_selected, 0);
+        assert_eq!(translation(&activity), Some(1));
+    }
+}
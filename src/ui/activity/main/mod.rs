@@ -3,8 +3,13 @@
 //! `main_activity` is the module which implements the Main activity, which is the activity to
 //! work on termusic app
 
+mod lyric_pair;
 mod playlist;
 mod queue;
+mod replaygain_scan;
+mod session;
+mod sleep_timer;
+mod stations;
 /**
  * MIT License
  *
@@ -39,18 +44,24 @@ mod youtube_options;
 use super::{Activity, Context, ExitReason};
 use crate::{
     config::{Termusic, MUSIC_DIR},
-    player::GStreamer,
-    song::Song,
+    player::{self, GeneralPlayer, NullPlayer},
+    song::{find_folder_cover, load_cover, FileFormat, Song},
     ui::activity::tageditor::TagEditorActivity,
 };
 use std::str::FromStr;
 // Ext
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 use log::error;
-use std::collections::VecDeque;
+use lyric_pair::{LyricPairs, LyricTranslation};
+use replaygain_scan::ReplayGainScanState;
+use serde::{Deserialize, Serialize};
+use session::Session;
+use sleep_timer::SleepTimer;
+use stations::StationList;
+use std::collections::{HashSet, VecDeque};
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::{self, Receiver, Sender};
-use std::thread::sleep;
+use std::thread::{self, sleep};
 use std::time::Duration;
 use tui_realm_treeview::Tree;
 use tuirealm::{Payload, Value, View};
@@ -69,6 +80,10 @@ const COMPONENT_TEXT_ERROR: &str = "TEXT_ERROR";
 const COMPONENT_CONFIRMATION_RADIO: &str = "CONFIRMATION_RADIO";
 const COMPONENT_CONFIRMATION_INPUT: &str = "CONFIRMATION_INPUT";
 const COMPONENT_TEXT_MESSAGE: &str = "TEXT_MESSAGE";
+const COMPONENT_RADIO_EQUALIZER: &str = "RADIO_EQUALIZER";
+const COMPONENT_RADIO_SLEEP_TIMER: &str = "RADIO_SLEEP_TIMER";
+const COMPONENT_TABLE_OUTPUT_DEVICE: &str = "TABLE_OUTPUT_DEVICE";
+const COMPONENT_TABLE_STATIONS: &str = "TABLE_STATIONS";
 
 /// ### `ViewLayout`
 ///
@@ -83,7 +98,7 @@ pub struct TermusicActivity {
     redraw: bool,
     path: PathBuf,
     tree: Tree,
-    pub player: GStreamer,
+    pub player: GeneralPlayer,
     queue_items: VecDeque<Song>,
     time_pos: u64,
     pub status: Option<Status>,
@@ -99,6 +114,34 @@ pub struct TermusicActivity {
     receiver_youtubesearch: Receiver<YoutubeSearchState>,
     sender_queueitems: Sender<VecDeque<Song>>,
     receiver_queueitems: Receiver<VecDeque<Song>>,
+    sender_replaygain: Sender<ReplayGainScanState>,
+    receiver_replaygain: Receiver<ReplayGainScanState>,
+    replaygain_scanning: bool,
+    loop_mode: LoopMode,
+    // file of the song that ends the pass in LoopMode::Once
+    once_marker: Option<String>,
+    stop_after_current: bool,
+    // session waiting for queue to be loaded
+    pending_session: Option<Session>,
+    sleep_timer: Option<SleepTimer>,
+    // sleep timer text shown in status line
+    sleep_timer_label: Option<String>,
+    // output devices listed in the popup
+    output_devices: Vec<String>,
+    // files failed to play, they are marked and skipped in queue
+    broken_songs: HashSet<String>,
+    // radio stations from stations.toml
+    stations: StationList,
+    // title sent by the station that's playing
+    stream_title: Option<String>,
+    // lyrics fill the screen, with album art on the left
+    lyric_fullscreen: bool,
+    // album art is printed by kitty
+    cover_shown: bool,
+    // lyric and translation chosen for each file, from lyric_pairs.toml
+    lyric_pairs: LyricPairs,
+    // lyric frame shown as translation of current lyric
+    lyric_translation: Option<LyricTranslation>,
 }
 
 pub enum MessageState {
@@ -113,6 +156,17 @@ pub enum Status {
     Paused,
 }
 
+// LoopMode decides what to play when a song finishes by itself
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoopMode {
+    #[default]
+    RepeatAll,
+    RepeatOne,
+    // stop when the whole queue is played
+    Once,
+}
+
 // TransferState is used to describe the status of download
 pub enum TransferState {
     Running, // indicates progress
@@ -146,6 +200,8 @@ impl Default for TermusicActivity {
         let (tx3, rx3): (Sender<YoutubeSearchState>, Receiver<YoutubeSearchState>) =
             mpsc::channel();
         let (tx4, rx4): (Sender<VecDeque<Song>>, Receiver<VecDeque<Song>>) = mpsc::channel();
+        let (tx5, rx5): (Sender<ReplayGainScanState>, Receiver<ReplayGainScanState>) =
+            mpsc::channel();
         Self {
             exit_reason: None,
             context: None,
@@ -153,7 +209,7 @@ impl Default for TermusicActivity {
             redraw: true, // Draw at first `on_draw`
             tree: Tree::new(Self::dir_tree(p, 3)),
             path: p.to_path_buf(),
-            player: GStreamer::new(),
+            player: GeneralPlayer::new(Box::new(NullPlayer::default())),
             queue_items: VecDeque::with_capacity(100),
             time_pos: 0,
             status: None,
@@ -169,6 +225,23 @@ impl Default for TermusicActivity {
             receiver_youtubesearch: rx3,
             sender_queueitems: tx4,
             receiver_queueitems: rx4,
+            sender_replaygain: tx5,
+            receiver_replaygain: rx5,
+            replaygain_scanning: false,
+            loop_mode: LoopMode::RepeatAll,
+            once_marker: None,
+            stop_after_current: false,
+            pending_session: None,
+            sleep_timer: None,
+            sleep_timer_label: None,
+            output_devices: Vec::new(),
+            broken_songs: HashSet::new(),
+            stations: StationList::default(),
+            stream_title: None,
+            lyric_fullscreen: false,
+            cover_shown: false,
+            lyric_pairs: LyricPairs::default(),
+            lyric_translation: None,
         }
     }
 }
@@ -180,7 +253,138 @@ impl TermusicActivity {
         let full_path = shellexpand::tilde(&music_dir);
         let p: &Path = Path::new(full_path.as_ref());
         self.scan_dir(p);
+        self.player
+            .set_backend(player::new_backend(self.config.player_backend));
+        self.player.set_crossfade(self.config.crossfade_duration);
+        self.player.set_replaygain_config(
+            self.config.replaygain,
+            self.config.replaygain_preamp,
+            self.config.replaygain_fallback,
+        );
+        if let Some(preset) = self.config.equalizer_preset() {
+            self.player.set_equalizer(&preset.bands);
+        }
+        if !self.config.output_device.is_empty() {
+            // keep the default device if the saved one is missing
+            if let Err(e) = self
+                .player
+                .set_output_device(Some(&self.config.output_device))
+            {
+                error!("Failed to set output device: {}", e);
+            }
+        }
     }
+
+    // apply_playback_rate sets the rate saved for song, or normal speed if there is none
+    pub fn apply_playback_rate(&mut self, song: &Song) {
+        let rate = song
+            .file()
+            .and_then(|f| self.config.playback_rate(f))
+            .unwrap_or(1.0);
+        self.player.set_rate(rate);
+    }
+
+    // save_playback_rate saves current rate as default of the selected file or folder
+    pub fn save_playback_rate(&mut self) {
+        if let Some(Payload::One(Value::Str(node_id))) = self.view.get_state(COMPONENT_TREEVIEW) {
+            let rate = self.player.rate();
+            self.config.set_playback_rate(&node_id, rate);
+            if let Err(e) = self.config.save() {
+                self.mount_error(format!("save config error: {}", e).as_str());
+                return;
+            }
+            Self::show_message(
+                &self.sender_message,
+                "Playback rate saved",
+                &format!("{:.1}x for {}", rate, node_id),
+            );
+        }
+    }
+
+    // embed_folder_cover embeds the folder image into all songs of the selected folder
+    pub fn embed_folder_cover(&mut self) {
+        let dir = match self.view.get_state(COMPONENT_TREEVIEW) {
+            Some(Payload::One(Value::Str(node_id))) => {
+                let p = PathBuf::from(node_id);
+                if p.is_dir() {
+                    p
+                } else if let Some(parent) = p.parent() {
+                    parent.to_path_buf()
+                } else {
+                    return;
+                }
+            }
+            _ => return,
+        };
+        let Some(cover) = find_folder_cover(&dir) else {
+            self.mount_error("no cover image found in folder");
+            return;
+        };
+        let picture = match load_cover(
+            &cover,
+            self.config.cover_max_size,
+            self.config.cover_quality,
+        ) {
+            Ok(picture) => picture,
+            Err(e) => {
+                self.mount_error(format!("load cover error: {}", e).as_str());
+                return;
+            }
+        };
+
+        let tx = self.sender_message.clone();
+        thread::spawn(move || {
+            let mut files: Vec<PathBuf> = std::fs::read_dir(&dir)
+                .map(|entries| entries.filter_map(|e| e.ok().map(|e| e.path())).collect())
+                .unwrap_or_default();
+            files.sort();
+            let mut tagged = 0;
+            let mut failed = 0;
+            for file in files {
+                let file = file.to_string_lossy();
+                if FileFormat::detect(&file).is_none() {
+                    continue;
+                }
+                match Song::from_str(&file).and_then(|mut s| s.save_cover(picture.clone())) {
+                    Ok(()) => tagged += 1,
+                    Err(_) => failed += 1,
+                }
+            }
+            Self::show_message(
+                &tx,
+                "Folder cover embedded",
+                &format!("{} songs tagged, {} failed", tagged, failed),
+            );
+        });
+    }
+
+    // set_equalizer_preset applies the preset and saves it as default
+    pub fn set_equalizer_preset(&mut self, index: usize) {
+        if let Some(preset) = self.config.equalizer_presets.get(index) {
+            self.player.set_equalizer(&preset.bands);
+            self.config.equalizer = preset.name.clone();
+            if let Err(e) = self.config.save() {
+                self.mount_error(format!("save config error: {}", e).as_str());
+            }
+        }
+    }
+
+    // set_output_device takes the index in output device popup, 0 is the default device
+    pub fn set_output_device(&mut self, index: usize) {
+        let name = match index {
+            0 => None,
+            i => self.output_devices.get(i - 1).cloned(),
+        };
+        if let Err(e) = self.player.set_output_device(name.as_deref()) {
+            self.mount_error(format!("output device error: {}", e).as_str());
+            return;
+        }
+        self.config.output_device = name.unwrap_or_default();
+        if let Err(e) = self.config.save() {
+            self.mount_error(format!("save config error: {}", e).as_str());
+        }
+    }
+
     pub fn run(&mut self) {
         match self.status {
             Some(Status::Stopped) => {
@@ -189,7 +393,7 @@ impl TermusicActivity {
                     return;
                 }
                 self.status = Some(Status::Running);
-                self.next_song();
+                self.next_song_on_finish();
             }
             Some(Status::Running | Status::Paused) => {}
             None => self.status = Some(Status::Stopped),
@@ -198,6 +402,7 @@ impl TermusicActivity {
 
     pub fn run_tageditor(&mut self) {
         let mut tageditor: TagEditorActivity = TagEditorActivity::default();
+        tageditor.set_cover_options(self.config.cover_max_size, self.config.cover_quality);
         if let Some(Payload::One(Value::Str(node_id))) = self.view.get_state(COMPONENT_TREEVIEW) {
             let p: &Path = Path::new(node_id.as_str());
             if p.is_dir() {
@@ -272,9 +477,19 @@ impl Activity for TermusicActivity {
         // // Init view
         self.init_setup();
 
+        // station names are needed by queue
+        if let Err(err) = self.load_stations() {
+            error!("Failed to load stations: {}", err);
+        }
+        if let Err(err) = self.load_lyric_pairs() {
+            error!("Failed to load lyric pairs: {}", err);
+        }
         if let Err(err) = self.load_queue() {
             error!("Failed to save queue: {}", err);
         }
+        if let Err(err) = self.load_session() {
+            error!("Failed to load session: {}", err);
+        }
         self.status = Some(Status::Stopped);
     }
 
@@ -324,6 +539,12 @@ impl Activity for TermusicActivity {
         if let Err(err) = self.save_queue() {
             error!("Failed to save queue: {}", err);
         }
+        if let Err(err) = self.save_session() {
+            error!("Failed to save session: {}", err);
+        }
+        if let Err(err) = self.save_lyric_pairs() {
+            error!("Failed to save lyric pairs: {}", err);
+        }
         // Disable raw mode
         if let Err(err) = disable_raw_mode() {
             error!("Failed to disable raw mode: {}", err);
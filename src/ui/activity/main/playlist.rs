@@ -82,6 +82,10 @@ impl TermusicActivity {
 
     pub fn sync_playlist(&mut self, node: Option<&str>) {
         self.tree = Tree::new(Self::dir_tree(self.path.as_ref(), 3));
+        // the file is changed, it may play now
+        if node.is_some_and(|file| self.broken_songs.remove(file)) {
+            self.sync_queue();
+        }
 
         if let Some(props) = self.view.get_props(COMPONENT_TREEVIEW) {
             let props = TreeViewPropsBuilder::from(props)
@@ -21,11 +21,13 @@
  * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
  * SOFTWARE.
  */
-use super::TermusicActivity;
+use super::{LoopMode, SleepTimer, TermusicActivity};
 use super::{COMPONENT_TABLE_QUEUE, COMPONENT_TREEVIEW};
 
 use crate::config::get_app_config_path;
 use crate::song::Song;
+#[cfg(feature = "mpris")]
+use crate::souvlaki::LoopStatus;
 use anyhow::Result;
 use humantime::format_duration;
 use rand::seq::SliceRandom;
@@ -56,7 +58,13 @@ impl TermusicActivity {
                 table.add_row();
             }
 
-            let duration = record.duration_formatted().to_string();
+            let duration = if self.is_broken(record) {
+                "broken".to_string()
+            } else if record.is_stream() {
+                "live".to_string()
+            } else {
+                record.duration_formatted().to_string()
+            };
             let duration_string = format!("[{:^6.6}]", duration);
 
             let noname_string = "No Name".to_string();
@@ -67,7 +75,13 @@ impl TermusicActivity {
             table
                 .add_col(TextSpan::new(duration_string.as_str()))
                 .add_col(TextSpan::new(artist).fg(tuirealm::tui::style::Color::LightYellow))
-                .add_col(TextSpan::new(title).bold())
+                .add_col(if self.is_broken(record) {
+                    TextSpan::new(title)
+                        .bold()
+                        .fg(tuirealm::tui::style::Color::Red)
+                } else {
+                    TextSpan::new(title).bold()
+                })
                 .add_col(TextSpan::new(record.album().unwrap_or("Unknown Album")));
         }
         if self.queue_items.is_empty() {
@@ -88,7 +102,20 @@ impl TermusicActivity {
             let msg = self.view.update(COMPONENT_TABLE_QUEUE, props);
             self.update(msg);
         }
+
+        // preload the next song for gapless playback, unless it'll be crossfaded
+        let next = self
+            .auto_next()
+            .filter(|song| !self.should_crossfade(song) && !self.is_broken(song))
+            .and_then(Song::file)
+            .map(String::from);
+        self.player.enqueue_next(next.as_deref());
+    }
+
+    pub fn is_broken(&self, song: &Song) -> bool {
+        song.file().is_some_and(|f| self.broken_songs.contains(f))
     }
+
     pub fn delete_item(&mut self, index: usize) {
         if self.queue_items.is_empty() {
             return;
@@ -134,11 +161,14 @@ impl TermusicActivity {
             .collect();
 
         let tx = self.sender_queueitems.clone();
+        let stations = self.stations.clone();
 
         thread::spawn(move || {
             let mut queue_items = VecDeque::new();
             for line in &lines {
-                if let Ok(s) = Song::from_str(line) {
+                if let Some(name) = stations.name(line) {
+                    queue_items.push_back(Song::from_station(name, line));
+                } else if let Ok(s) = Song::from_str(line) {
                     queue_items.push_back(s);
                 };
             }
@@ -155,12 +185,8 @@ impl TermusicActivity {
     }
 
     pub fn update_item_delete(&mut self) {
-        self.queue_items.retain(|x| {
-            x.file().map_or(false, |p| {
-                let path = Path::new(p);
-                path.exists()
-            })
-        });
+        self.queue_items
+            .retain(|x| x.is_stream() || x.file().is_some_and(|p| Path::new(p).exists()));
 
         self.sync_queue();
         self.view.active(COMPONENT_TREEVIEW);
@@ -170,10 +196,108 @@ impl TermusicActivity {
         for v in &self.queue_items {
             duration += v.duration();
         }
+        let loop_mode = match self.loop_mode {
+            LoopMode::RepeatAll => "Repeat all",
+            LoopMode::RepeatOne => "Repeat one",
+            LoopMode::Once => "Play once",
+        };
+        let stop_after_current = if self.stop_after_current {
+            " | Stop after current"
+        } else {
+            ""
+        };
         format!(
-            "\u{2500} Queue \u{2500}\u{2500}\u{2500}\u{2524} Total {} songs | {} \u{251c}\u{2500}",
+            "\u{2500} Queue \u{2500}\u{2500}\u{2500}\u{2524} Total {} songs | {} | {}{} \u{251c}\u{2500}",
             self.queue_items.len(),
-            format_duration(Duration::new(duration.as_secs(), 0))
+            format_duration(Duration::new(duration.as_secs(), 0)),
+            loop_mode,
+            stop_after_current,
         )
     }
+
+    // auto_next returns the song to play when current one finishes by itself, None means
+    // playback should stop
+    pub fn auto_next(&self) -> Option<&Song> {
+        if self.stop_after_current {
+            return None;
+        }
+        match self.loop_mode {
+            LoopMode::RepeatAll => self.queue_items.front(),
+            LoopMode::RepeatOne => self.current_song.as_ref(),
+            LoopMode::Once => self
+                .queue_items
+                .front()
+                .filter(|s| s.file().is_none() || s.file() != self.once_marker.as_deref()),
+        }
+    }
+
+    // next_song_on_finish moves on after current song ended, following the loop mode
+    pub fn next_song_on_finish(&mut self) {
+        if self.current_song.is_none() {
+            self.next_song();
+            return;
+        }
+        if self.auto_next().is_none() {
+            self.stop_after_current = false;
+            self.cue_next_song();
+            return;
+        }
+        if self.loop_mode == LoopMode::RepeatOne {
+            // current song is at the back of queue
+            if let Some(song) = self.queue_items.pop_back() {
+                self.queue_items.push_front(song);
+            }
+        }
+        self.next_song();
+    }
+
+    pub fn cycle_loop_mode(&mut self) {
+        self.set_loop_mode(match self.loop_mode {
+            LoopMode::RepeatAll => LoopMode::RepeatOne,
+            LoopMode::RepeatOne => LoopMode::Once,
+            LoopMode::Once => LoopMode::RepeatAll,
+        });
+    }
+
+    pub fn set_loop_mode(&mut self, loop_mode: LoopMode) {
+        self.loop_mode = loop_mode;
+        if loop_mode == LoopMode::Once {
+            // the pass ends when the song playing now comes around again
+            self.once_marker = self
+                .current_song
+                .as_ref()
+                .or_else(|| self.queue_items.front())
+                .and_then(Song::file)
+                .map(String::from);
+        }
+        self.update_loop_status();
+        self.sync_queue();
+    }
+
+    pub fn toggle_stop_after_current(&mut self) {
+        self.stop_after_current = !self.stop_after_current;
+        if !self.stop_after_current && matches!(self.sleep_timer, Some(SleepTimer::EndOfTrack)) {
+            // sleep timer at end of track relies on stopping after current
+            self.set_sleep_timer(0);
+        }
+        self.sync_queue();
+    }
+
+    // update_loop_status tells mpris clients about the loop mode
+    #[cfg_attr(
+        not(feature = "mpris"),
+        allow(
+            clippy::unused_self,
+            clippy::missing_const_for_fn,
+            clippy::needless_pass_by_ref_mut
+        )
+    )]
+    pub fn update_loop_status(&mut self) {
+        #[cfg(feature = "mpris")]
+        self.player.set_loop_status(match self.loop_mode {
+            LoopMode::RepeatAll => LoopStatus::Playlist,
+            LoopMode::RepeatOne => LoopStatus::Track,
+            LoopMode::Once => LoopStatus::None,
+        });
+    }
 }
@@ -0,0 +1,150 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use super::{TermusicActivity, COMPONENT_LABEL_HELP};
+use crate::song::replaygain::{album_gain, analyze_track, ReplayGain, TrackLoudness};
+use crate::song::{FileFormat, Song};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::thread;
+use tui_realm_stdlib::LabelPropsBuilder;
+use tui_realm_treeview::Node;
+use tuirealm::PropsBuilder;
+
+// how deep the library is walked from current path
+const SCAN_DEPTH: usize = 8;
+
+// ReplayGainScanState is used to describe the progress of replaygain analysis
+pub enum ReplayGainScanState {
+    Running(usize, usize),   // finished and total albums
+    Completed(usize, usize), // tagged and failed songs
+}
+
+impl TermusicActivity {
+    // replaygain_scan analyzes all songs under current path in background. Songs in the same
+    // folder are treated as one album. Folders where every song is tagged already are
+    // skipped unless force is set.
+    pub fn replaygain_scan(&mut self, force: bool) {
+        if self.replaygain_scanning {
+            self.mount_error("replaygain analysis is running");
+            return;
+        }
+        self.replaygain_scanning = true;
+        let path = self.path.clone();
+        let tx = self.sender_replaygain.clone();
+        thread::spawn(move || {
+            let mut albums: BTreeMap<PathBuf, Vec<String>> = BTreeMap::new();
+            collect_songs(&Self::dir_tree(&path, SCAN_DEPTH), &mut albums);
+
+            let total = albums.len();
+            let mut tagged = 0;
+            let mut failed = 0;
+            for (index, files) in albums.into_values().enumerate() {
+                let _drop = tx.send(ReplayGainScanState::Running(index, total));
+                let songs: Vec<Song> = files
+                    .iter()
+                    .filter_map(|f| Song::from_str(f).ok())
+                    .collect();
+                if !force && songs.iter().all(|s| !s.replaygain().is_empty()) {
+                    continue;
+                }
+                let mut results: Vec<(Song, TrackLoudness)> = vec![];
+                for song in songs {
+                    match song.file().map(analyze_track) {
+                        Some(Ok(loudness)) => results.push((song, loudness)),
+                        _ => failed += 1,
+                    }
+                }
+
+                let loudness: Vec<TrackLoudness> = results.iter().map(|(_, l)| *l).collect();
+                let (album_gain, album_peak) = album_gain(&loudness);
+                for (mut song, l) in results {
+                    let replaygain = ReplayGain {
+                        track_gain: Some(l.gain),
+                        track_peak: Some(l.peak),
+                        album_gain: Some(album_gain),
+                        album_peak: Some(album_peak),
+                    };
+                    match song.save_replaygain(replaygain) {
+                        Ok(()) => tagged += 1,
+                        Err(_) => failed += 1,
+                    }
+                }
+            }
+            let _drop = tx.send(ReplayGainScanState::Completed(tagged, failed));
+        });
+    }
+
+    // update_replaygain_scan shows the analysis progress in status bar
+    pub fn update_replaygain_scan(&mut self) {
+        if let Ok(state) = self.receiver_replaygain.try_recv() {
+            match state {
+                ReplayGainScanState::Running(finished, total) => {
+                    let text = format!(" Analyzing replaygain: {}/{} albums", finished, total);
+                    if let Some(props) = self.view.get_props(COMPONENT_LABEL_HELP) {
+                        let props = LabelPropsBuilder::from(props)
+                            .with_text(text)
+                            .with_foreground(tuirealm::tui::style::Color::White)
+                            .with_background(tuirealm::tui::style::Color::Blue)
+                            .build();
+
+                        let msg = self.view.update(COMPONENT_LABEL_HELP, props);
+                        self.update(msg);
+                        self.redraw = true;
+                    }
+                }
+                ReplayGainScanState::Completed(tagged, failed) => {
+                    self.replaygain_scanning = false;
+                    self.update_status_line(super::StatusLine::Default);
+                    if failed > 0 {
+                        self.mount_error(&format!(
+                            "replaygain: {} songs tagged, {} failed",
+                            tagged, failed
+                        ));
+                    }
+                    self.sync_playlist(None);
+                }
+            }
+        }
+    }
+}
+
+// collect_songs groups all supported files under node by their folder
+fn collect_songs(node: &Node, albums: &mut BTreeMap<PathBuf, Vec<String>>) {
+    let p: &Path = Path::new(node.id());
+    if p.is_dir() {
+        let mut index = 0;
+        while let Some(child) = node.node_by_route(&[index]) {
+            collect_songs(child, albums);
+            index += 1;
+        }
+        return;
+    }
+    if let (Some(parent), Some(_)) = (p.parent(), FileFormat::detect(node.id())) {
+        albums
+            .entry(parent.to_path_buf())
+            .or_default()
+            .push(node.id().to_string());
+    }
+}
@@ -0,0 +1,224 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use super::{LoopMode, TermusicActivity};
+use crate::config::get_app_config_path;
+use crate::song::Song;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, read_to_string};
+
+// Session is the playback state saved next to queue.log, so that termusic could go on from
+// where it was stopped
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct Session {
+    // index of current song in queue
+    pub current: Option<usize>,
+    pub position_ms: u64,
+    pub volume: f64,
+    pub loop_mode: LoopMode,
+    pub stop_after_current: bool,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            current: None,
+            position_ms: 0,
+            volume: 0.5,
+            loop_mode: LoopMode::RepeatAll,
+            stop_after_current: false,
+        }
+    }
+}
+
+impl TermusicActivity {
+    pub fn save_session(&self) -> Result<()> {
+        let mut path = get_app_config_path()?;
+        path.push("session.toml");
+        fs::write(path, toml::to_string(&self.session())?)?;
+        Ok(())
+    }
+
+    // session is the playback state to save
+    fn session(&self) -> Session {
+        // current song is normally at the back of queue
+        let current = self.current_song.as_ref().and_then(|song| {
+            self.queue_items
+                .iter()
+                .rposition(|s| s.file().is_some() && s.file() == song.file())
+        });
+        Session {
+            current,
+            position_ms: self.player.position_ms(),
+            volume: self.player.volume(),
+            loop_mode: self.loop_mode,
+            stop_after_current: self.stop_after_current,
+        }
+    }
+
+    // load_session restores volume and loop mode at once, current song is restored after queue
+    // is loaded
+    pub fn load_session(&mut self) -> Result<()> {
+        let mut path = get_app_config_path()?;
+        path.push("session.toml");
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let session: Session = toml::from_str(&read_to_string(path)?)?;
+        self.player.set_volume(session.volume);
+        self.stop_after_current = session.stop_after_current;
+        self.set_loop_mode(session.loop_mode);
+        if self.config.resume_playback {
+            self.pending_session = Some(session);
+        }
+        Ok(())
+    }
+
+    // restore_session loads the saved song paused at the saved position
+    pub fn restore_session(&mut self) {
+        let Some(session) = self.pending_session.take() else {
+            return;
+        };
+        let index = match session.current {
+            Some(i) if i < self.queue_items.len() => i,
+            _ => return,
+        };
+
+        // move current song to the front, so that it's cued as the next song
+        self.queue_items.rotate_left(index);
+        self.cue_next_song();
+        // streams are live, they can't go on from the saved position
+        if !self.current_song.as_ref().is_some_and(Song::is_stream) {
+            self.player.seek_to_ms(session.position_ms);
+        }
+        self.update_progress();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::{GeneralPlayer, NullPlayer};
+    use pretty_assertions::assert_eq;
+    use std::str::FromStr;
+
+    // activity builds an activity with a null player and given songs in queue
+    fn activity(files: &[&str]) -> (TermusicActivity, NullPlayer) {
+        let backend = NullPlayer::default();
+        let mut activity = TermusicActivity {
+            player: GeneralPlayer::new(Box::new(backend.clone())),
+            ..TermusicActivity::default()
+        };
+        activity.queue_items = files
+            .iter()
+            .map(|f| {
+                if f.starts_with("http") {
+                    Song::from_station(f, f)
+                } else {
+                    Song::from_str(f).unwrap()
+                }
+            })
+            .collect();
+        (activity, backend)
+    }
+
+    fn queue(activity: &TermusicActivity) -> Vec<&str> {
+        activity.queue_items.iter().filter_map(Song::file).collect()
+    }
+
+    #[test]
+    fn test_restore_session() {
+        let (mut activity, backend) = activity(&["/a.mp3", "/b.mp3", "/c.mp3"]);
+        activity.pending_session = Some(Session {
+            current: Some(1),
+            position_ms: 62_500,
+            ..Session::default()
+        });
+        activity.restore_session();
+
+        // queue goes on from the saved song, which is cued paused at the saved position
+        assert_eq!(queue(&activity), vec!["/c.mp3", "/a.mp3", "/b.mp3"]);
+        assert_eq!(
+            activity.current_song.as_ref().and_then(Song::file),
+            Some("/b.mp3")
+        );
+        let state = backend.state.borrow();
+        assert_eq!(state.file.as_deref(), Some("/b.mp3"));
+        assert!(state.paused);
+        assert_eq!(state.position_ms, 62_500);
+        drop(state);
+        assert_eq!(activity.pending_session, None);
+    }
+
+    #[test]
+    fn test_restore_session_out_of_range() {
+        let (mut activity, backend) = activity(&["/a.mp3", "/b.mp3"]);
+        activity.pending_session = Some(Session {
+            current: Some(2),
+            position_ms: 62_500,
+            ..Session::default()
+        });
+        activity.restore_session();
+
+        assert_eq!(queue(&activity), vec!["/a.mp3", "/b.mp3"]);
+        assert!(activity.current_song.is_none());
+        assert_eq!(backend.state.borrow().file, None);
+        assert_eq!(backend.state.borrow().position_ms, 0);
+    }
+
+    #[test]
+    fn test_restore_session_stream() {
+        let url = "http://localhost:8000/stream";
+        let (mut activity, backend) = activity(&["/a.mp3", url]);
+        activity.pending_session = Some(Session {
+            current: Some(1),
+            position_ms: 62_500,
+            ..Session::default()
+        });
+        activity.restore_session();
+
+        // streams are cued, but never seeked
+        assert_eq!(backend.state.borrow().file.as_deref(), Some(url));
+        assert!(backend.state.borrow().paused);
+        assert_eq!(backend.state.borrow().position_ms, 0);
+    }
+
+    #[test]
+    fn test_session() {
+        // the same song queued twice, current one is the last
+        let (mut activity, backend) = activity(&["/a.mp3", "/b.mp3", "/a.mp3", "/c.mp3"]);
+        activity.current_song = Some(Song::from_str("/a.mp3").unwrap());
+        backend.state.borrow_mut().position_ms = 1_000;
+        let session = activity.session();
+        assert_eq!(session.current, Some(2));
+        assert_eq!(session.position_ms, 1_000);
+
+        activity.current_song = Some(Song::from_str("/d.mp3").unwrap());
+        assert_eq!(activity.session().current, None);
+        activity.current_song = None;
+        assert_eq!(activity.session().current, None);
+    }
+}
@@ -0,0 +1,148 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use super::{Status, StatusLine, TermusicActivity};
+use crate::player::FADE_OUT_DURATION;
+use crate::ui::activity::ExitReason;
+use std::time::{Duration, Instant};
+
+pub const SLEEP_TIMER_OPTIONS: [&str; 8] = [
+    "Off",
+    "15 min",
+    "30 min",
+    "45 min",
+    "60 min",
+    "90 min",
+    "End of track",
+    "End of queue",
+];
+const SLEEP_TIMER_MINUTES: [u64; 5] = [15, 30, 45, 60, 90];
+
+pub enum SleepTimer {
+    At(Instant),
+    EndOfTrack,
+    // file of the last song in queue
+    EndOfQueue(Option<String>),
+}
+
+impl TermusicActivity {
+    // set_sleep_timer takes the index of SLEEP_TIMER_OPTIONS
+    pub fn set_sleep_timer(&mut self, index: usize) {
+        self.player.cancel_fade_out();
+        if matches!(self.sleep_timer, Some(SleepTimer::EndOfTrack)) {
+            self.stop_after_current = false;
+        }
+        self.sleep_timer = match index {
+            1..=5 => Some(SleepTimer::At(
+                Instant::now() + Duration::from_secs(SLEEP_TIMER_MINUTES[index - 1] * 60),
+            )),
+            6 => {
+                self.stop_after_current = true;
+                Some(SleepTimer::EndOfTrack)
+            }
+            // current song is at the back of queue, so the one before it is played last
+            7 => Some(SleepTimer::EndOfQueue(
+                self.queue_items
+                    .iter()
+                    .rev()
+                    .nth(1)
+                    .or_else(|| self.queue_items.back())
+                    .and_then(|s| s.file())
+                    .map(String::from),
+            )),
+            _ => None,
+        };
+        self.sync_queue();
+        self.update_status_line(StatusLine::Default);
+    }
+
+    // update_sleep_timer counts down and fades out playback when the timer fires
+    pub fn update_sleep_timer(&mut self) {
+        match &self.sleep_timer {
+            None => return,
+            Some(SleepTimer::At(deadline)) => {
+                if Instant::now() >= *deadline {
+                    self.player.start_fade_out();
+                }
+            }
+            Some(SleepTimer::EndOfTrack) => {
+                if !self.stop_after_current {
+                    // the song ended before fading out
+                    self.fire_sleep_timer();
+                    return;
+                }
+                let (_, time_pos, duration) = self.player.get_progress();
+                if matches!(self.status, Some(Status::Running))
+                    && time_pos + FADE_OUT_DURATION >= duration
+                {
+                    self.player.start_fade_out();
+                }
+            }
+            Some(SleepTimer::EndOfQueue(last)) => {
+                let current = self.current_song.as_ref().and_then(|s| s.file());
+                if last.is_none() || current == last.as_deref() {
+                    self.sleep_timer = Some(SleepTimer::EndOfTrack);
+                    self.stop_after_current = true;
+                    self.sync_queue();
+                }
+            }
+        }
+
+        if self.player.update_fade_out() {
+            if matches!(self.sleep_timer, Some(SleepTimer::EndOfTrack)) {
+                // cue next song, so that playback goes on from there
+                self.next_song_on_finish();
+            }
+            self.fire_sleep_timer();
+            return;
+        }
+
+        let label = self.sleep_timer_label();
+        if label != self.sleep_timer_label && !self.replaygain_scanning {
+            self.update_status_line(StatusLine::Default);
+        }
+    }
+
+    fn fire_sleep_timer(&mut self) {
+        // the song may end before fading out is finished, volume is restored for the next play
+        self.player.cancel_fade_out();
+        self.sleep_timer = None;
+        self.status = Some(Status::Paused);
+        self.update_status_line(StatusLine::Default);
+        if self.config.sleep_timer_quit {
+            self.exit_reason = Some(ExitReason::Quit);
+        }
+    }
+
+    // sleep_timer_label is shown in status line
+    pub fn sleep_timer_label(&self) -> Option<String> {
+        match self.sleep_timer.as_ref()? {
+            SleepTimer::At(deadline) => {
+                let secs = deadline.saturating_duration_since(Instant::now()).as_secs();
+                Some(format!("Sleep in {:02}:{:02}", secs / 60, secs % 60))
+            }
+            SleepTimer::EndOfTrack => Some("Sleep at end of track".to_string()),
+            SleepTimer::EndOfQueue(_) => Some("Sleep at end of queue".to_string()),
+        }
+    }
+}
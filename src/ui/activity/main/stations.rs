@@ -0,0 +1,227 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use super::TermusicActivity;
+use crate::config::get_app_config_path;
+use crate::song::Song;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, read_to_string};
+use std::path::Path;
+
+// written on first start, so that users know what stations.toml looks like
+const STATIONS_TEMPLATE: &str = r#"# Radio stations listed by <w>. Each one needs a name and a http or https url, e.g.
+#
+# [[stations]]
+# name = "Local Icecast"
+# url = "http://localhost:8000/stream"
+"#;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct Station {
+    pub name: String,
+    pub url: String,
+}
+
+// StationList is the content of stations.toml in config directory
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default)]
+pub struct StationList {
+    pub stations: Vec<Station>,
+}
+
+impl StationList {
+    // load reads stations.toml in dir, the template is written there on first start
+    fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join("stations.toml");
+        if !path.exists() {
+            fs::write(path, STATIONS_TEMPLATE)?;
+            return Ok(Self::default());
+        }
+
+        Ok(toml::from_str(&read_to_string(path)?)?)
+    }
+
+    // name of the station with url, streams added by url only are shown as is
+    pub fn name(&self, url: &str) -> Option<&str> {
+        self.stations
+            .iter()
+            .find(|s| s.url == url)
+            .map(|s| s.name.as_str())
+    }
+}
+
+impl TermusicActivity {
+    pub fn load_stations(&mut self) -> Result<()> {
+        self.stations = StationList::load(&get_app_config_path()?)?;
+        Ok(())
+    }
+
+    // add_station puts the station with index in popup to the queue, as the next song
+    pub fn add_station(&mut self, index: usize) {
+        if let Some(station) = self.stations.stations.get(index) {
+            let song = Song::from_station(&station.name, &station.url);
+            self.add_queue(song);
+        }
+    }
+
+    // start_stream clears the title of last station, and shows the station name over mpris
+    // until a title is sent
+    pub fn start_stream(&mut self, song: &Song) {
+        self.stream_title = None;
+        if song.is_stream() {
+            if let Some(station) = song.title() {
+                self.player.update_stream_metadata(station, None);
+            }
+        }
+    }
+
+    // update_stream_title shows the title sent by the station that's playing
+    pub fn update_stream_title(&mut self, title: String) {
+        let Some(station) = self
+            .current_song
+            .as_ref()
+            .filter(|s| s.is_stream())
+            .and_then(|s| s.title().map(String::from))
+        else {
+            return;
+        };
+        self.player
+            .update_stream_metadata(&station, Some(title.as_str()));
+        self.stream_title = Some(title);
+        self.update_progress_title();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::is_stream;
+    use anyhow::anyhow;
+    use pretty_assertions::assert_eq;
+    use std::io::{self, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::Duration;
+    use ureq::AgentBuilder;
+
+    // fetch_stream_title is a minimal ICY client, it asks for metadata the way souphttpsrc does
+    // and reads the StreamTitle of the first block, which comes after icy-metaint bytes of audio
+    fn fetch_stream_title(url: &str) -> Result<Option<String>> {
+        let agent = AgentBuilder::new().timeout(Duration::from_secs(10)).build();
+        let response = agent.get(url).set("Icy-MetaData", "1").call()?;
+        let metaint: u64 = response
+            .header("icy-metaint")
+            .ok_or_else(|| anyhow!("no icy metadata sent by {}", url))?
+            .parse()?;
+
+        let mut reader = response.into_reader();
+        io::copy(&mut reader.by_ref().take(metaint), &mut io::sink())?;
+        let mut length = [0_u8];
+        reader.read_exact(&mut length)?;
+        let mut metadata = vec![0; usize::from(length[0]) * 16];
+        reader.read_exact(&mut metadata)?;
+        Ok(parse_stream_title(&String::from_utf8_lossy(&metadata)))
+    }
+
+    // parse_stream_title gets the title out of metadata like StreamTitle='Artist - Title';
+    // The block is padded with zeros, and the title may contain quotes itself.
+    fn parse_stream_title(metadata: &str) -> Option<String> {
+        let start = metadata.find("StreamTitle='")? + "StreamTitle='".len();
+        let rest = &metadata[start..];
+        let title = rest.find("';").map_or_else(
+            || rest.trim_end_matches('\0').trim_end_matches('\''),
+            |end| &rest[..end],
+        );
+        if title.is_empty() {
+            return None;
+        }
+        Some(title.to_string())
+    }
+
+    #[test]
+    fn test_load_stations() {
+        let dir = std::env::temp_dir().join(format!("termusic-stations-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("stations.toml");
+
+        // the template is written on first start, and read back as an empty list
+        assert_eq!(StationList::load(&dir).unwrap(), StationList::default());
+        assert_eq!(read_to_string(&path).unwrap(), STATIONS_TEMPLATE);
+        assert_eq!(StationList::load(&dir).unwrap(), StationList::default());
+
+        let station = r#"
+[[stations]]
+name = "Local Icecast"
+url = "http://localhost:8000/stream"
+"#;
+        fs::write(&path, [STATIONS_TEMPLATE, station].concat()).unwrap();
+        let list = StationList::load(&dir).unwrap();
+        assert_eq!(
+            list.name("http://localhost:8000/stream"),
+            Some("Local Icecast")
+        );
+        assert_eq!(list.name("http://localhost:8000/other"), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_stream_title() {
+        // a local stand-in for icecast, sending 16 bytes of audio between metadata blocks
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/stream.mp3", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0_u8; 1024];
+            let n = stream.read(&mut request).unwrap();
+            assert!(String::from_utf8_lossy(&request[..n]).contains("Icy-MetaData: 1"));
+
+            let mut metadata = b"StreamTitle='Artist - It\'s a Title';StreamUrl='';".to_vec();
+            metadata.resize(64, 0);
+            stream
+                .write_all(
+                    b"HTTP/1.0 200 OK\r\nContent-Type: audio/mpeg\r\nicy-metaint: 16\r\n\r\n",
+                )
+                .unwrap();
+            stream.write_all(&[0xff; 16]).unwrap();
+            stream.write_all(&[4]).unwrap();
+            stream.write_all(&metadata).unwrap();
+            stream.write_all(&[0xff; 16]).unwrap();
+        });
+
+        let song = Song::from_station("Local Icecast", &url);
+        assert!(song.is_stream());
+        assert!(is_stream(song.file().unwrap()));
+        assert_eq!(
+            fetch_stream_title(&url).unwrap(),
+            Some("Artist - It's a Title".to_string())
+        );
+        server.join().unwrap();
+
+        assert_eq!(parse_stream_title("StreamTitle='';\0\0"), None);
+        assert_eq!(
+            parse_stream_title("StreamTitle='No Url'\0\0"),
+            Some("No Url".to_string())
+        );
+    }
+}
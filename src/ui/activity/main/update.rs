@@ -25,36 +25,44 @@
 use std::str::FromStr;
 // ext
 use super::{
-    youtube_options::YoutubeSearchState, ExitReason, MessageState, Status, StatusLine,
+    youtube_options::YoutubeSearchState, ExitReason, LoopMode, MessageState, Status, StatusLine,
     TermusicActivity, TransferState, COMPONENT_CONFIRMATION_INPUT, COMPONENT_CONFIRMATION_RADIO,
     COMPONENT_INPUT_URL, COMPONENT_LABEL_HELP, COMPONENT_PARAGRAPH_LYRIC, COMPONENT_PROGRESS,
-    COMPONENT_TABLE_QUEUE, COMPONENT_TABLE_YOUTUBE, COMPONENT_TEXT_ERROR, COMPONENT_TEXT_HELP,
-    COMPONENT_TREEVIEW,
+    COMPONENT_RADIO_EQUALIZER, COMPONENT_RADIO_SLEEP_TIMER, COMPONENT_TABLE_OUTPUT_DEVICE,
+    COMPONENT_TABLE_QUEUE, COMPONENT_TABLE_STATIONS, COMPONENT_TABLE_YOUTUBE, COMPONENT_TEXT_ERROR,
+    COMPONENT_TEXT_HELP, COMPONENT_TREEVIEW,
 };
 use crate::{
+    player::{is_stream, PlayerMsg, PlayerState},
     song::Song,
     songtag::lrc::Lyric,
+    ui::components::lyric::LyricPropsBuilder,
     ui::keymap::{
-        MSG_KEY_BACKSPACE, MSG_KEY_CHAR_B, MSG_KEY_CHAR_CAPITAL_B, MSG_KEY_CHAR_CAPITAL_D,
-        MSG_KEY_CHAR_CAPITAL_F, MSG_KEY_CHAR_CAPITAL_G, MSG_KEY_CHAR_CAPITAL_L,
-        MSG_KEY_CHAR_CAPITAL_N, MSG_KEY_CHAR_CAPITAL_Q, MSG_KEY_CHAR_CAPITAL_T, MSG_KEY_CHAR_D,
-        MSG_KEY_CHAR_DASH, MSG_KEY_CHAR_EQUAL, MSG_KEY_CHAR_F, MSG_KEY_CHAR_G, MSG_KEY_CHAR_H,
-        MSG_KEY_CHAR_J, MSG_KEY_CHAR_K, MSG_KEY_CHAR_L, MSG_KEY_CHAR_MINUS, MSG_KEY_CHAR_N,
-        MSG_KEY_CHAR_P, MSG_KEY_CHAR_PLUS, MSG_KEY_CHAR_R, MSG_KEY_CHAR_S, MSG_KEY_CHAR_T,
-        MSG_KEY_CHAR_Y, MSG_KEY_CTRL_H, MSG_KEY_ENTER, MSG_KEY_ESC, MSG_KEY_SHIFT_TAB,
-        MSG_KEY_SPACE, MSG_KEY_TAB,
+        MSG_KEY_BACKSPACE, MSG_KEY_CHAR_A, MSG_KEY_CHAR_B, MSG_KEY_CHAR_CAPITAL_A,
+        MSG_KEY_CHAR_CAPITAL_B, MSG_KEY_CHAR_CAPITAL_C, MSG_KEY_CHAR_CAPITAL_D,
+        MSG_KEY_CHAR_CAPITAL_F, MSG_KEY_CHAR_CAPITAL_G, MSG_KEY_CHAR_CAPITAL_K,
+        MSG_KEY_CHAR_CAPITAL_L, MSG_KEY_CHAR_CAPITAL_M, MSG_KEY_CHAR_CAPITAL_N,
+        MSG_KEY_CHAR_CAPITAL_Q, MSG_KEY_CHAR_CAPITAL_T, MSG_KEY_CHAR_CAPITAL_X, MSG_KEY_CHAR_D,
+        MSG_KEY_CHAR_DASH, MSG_KEY_CHAR_E, MSG_KEY_CHAR_EQUAL, MSG_KEY_CHAR_F, MSG_KEY_CHAR_G,
+        MSG_KEY_CHAR_H, MSG_KEY_CHAR_J, MSG_KEY_CHAR_K, MSG_KEY_CHAR_L, MSG_KEY_CHAR_LEFT_BRACKET,
+        MSG_KEY_CHAR_M, MSG_KEY_CHAR_MINUS, MSG_KEY_CHAR_N, MSG_KEY_CHAR_O, MSG_KEY_CHAR_P,
+        MSG_KEY_CHAR_PLUS, MSG_KEY_CHAR_R, MSG_KEY_CHAR_RIGHT_BRACKET, MSG_KEY_CHAR_S,
+        MSG_KEY_CHAR_T, MSG_KEY_CHAR_V, MSG_KEY_CHAR_W, MSG_KEY_CHAR_X, MSG_KEY_CHAR_Y,
+        MSG_KEY_CHAR_Z, MSG_KEY_CTRL_H, MSG_KEY_CTRL_T, MSG_KEY_ENTER, MSG_KEY_ESC,
+        MSG_KEY_SHIFT_TAB, MSG_KEY_SPACE, MSG_KEY_TAB,
     },
 };
 use humantime::format_duration;
+use log::warn;
+use std::convert::TryFrom;
 use std::path::{Path, PathBuf};
-use std::thread::{self, sleep};
 use std::time::Duration;
-use tui_realm_stdlib::{LabelPropsBuilder, ParagraphPropsBuilder, ProgressBarPropsBuilder};
+use tui_realm_stdlib::{LabelPropsBuilder, ProgressBarPropsBuilder};
 use tui_realm_treeview::TreeViewPropsBuilder;
 use tuirealm::{
     event::{Event, KeyCode, KeyEvent, KeyModifiers},
     props::TextSpan,
-    tui::layout::Alignment,
+    tui::{layout::Alignment, style::Color},
     Msg, Payload, PropsBuilder, Value,
 };
 
@@ -156,6 +164,7 @@ impl TermusicActivity {
                            if let Err(e) = song.save_tag() {
                                self.mount_error(e.to_string().as_ref());
                            };
+                           self.align_translation();
                        }
                 }
                    None
@@ -171,6 +180,7 @@ impl TermusicActivity {
                            if let Err(e) = song.save_tag() {
                                self.mount_error(e.to_string().as_ref());
                            };
+                           self.align_translation();
                         }
                 }
                     None
@@ -244,12 +254,18 @@ impl TermusicActivity {
                             let p: &Path = Path::new(node_id.as_str());
                             if p.is_dir() {
                                 let new_items = Self::dir_children(p);
-                                for i in new_items.iter().rev() {
+                                let mut songs = vec![];
+                                for i in &new_items {
                                     match Song::from_str(i) {
-                                        Ok(s) => self.add_queue(s),
+                                        Ok(s) => songs.push(s),
                                         Err(e) => self.mount_error(format!("add queue error: {}",e).as_str()),
                                     };
                                 }
+                                // albums are added in track order, songs without track number keep the file order
+                                songs.sort_by_key(|s| (s.fields().disc, s.fields().track));
+                                for s in songs.into_iter().rev() {
+                                    self.add_queue(s);
+                                }
                             }
                     }
                             None
@@ -258,11 +274,19 @@ impl TermusicActivity {
                 (COMPONENT_TABLE_QUEUE,key) if key==  &MSG_KEY_CHAR_L => {
                     if let Some(Payload::One(Value::Usize(index))) = self.view.get_state(COMPONENT_TABLE_QUEUE) {
                         self.time_pos = 0;
-                        if let Some(song) = self.queue_items.get(index) {
+                        if let Some(song) = self.queue_items.get(index).cloned() {
                             if let Some(file) = song.file() {
+                                // a song played on purpose is given another try
+                                if self.broken_songs.remove(file) {
+                                    self.sync_queue();
+                                }
                                 self.player.queue_and_play(file);
                             }
-                            self.current_song = Some(song.clone());
+                            self.player.apply_replaygain(song.replaygain());
+                            self.apply_playback_rate(&song);
+                            self.current_song = Some(song);
+                            self.apply_lyric_pair();
+                            self.update_progress_title();
                         }
                         self.update_photo();
                     }
@@ -313,12 +337,39 @@ impl TermusicActivity {
                     None
                 }
 
+                // loop mode
+                (_,key) if key==  &MSG_KEY_CHAR_M => {
+                    self.cycle_loop_mode();
+                    None
+                }
+                (_,key) if key==  &MSG_KEY_CHAR_CAPITAL_M => {
+                    self.toggle_stop_after_current();
+                    None
+                }
+
                 // start download
                 (COMPONENT_TREEVIEW,key) if key==  &MSG_KEY_CHAR_S => {
                     self.mount_youtube_url();
                     None
                 }
 
+                // analyze replaygain, A to re-scan tagged songs as well
+                (COMPONENT_TREEVIEW,key) if key==  &MSG_KEY_CHAR_A => {
+                    self.replaygain_scan(false);
+                    None
+                }
+
+                (COMPONENT_TREEVIEW,key) if key==  &MSG_KEY_CHAR_CAPITAL_A => {
+                    self.replaygain_scan(true);
+                    None
+                }
+
+                // embed folder image as cover of all songs in folder
+                (COMPONENT_TREEVIEW,key) if key==  &MSG_KEY_CHAR_CAPITAL_C => {
+                    self.embed_folder_cover();
+                    None
+                }
+
                 (COMPONENT_TREEVIEW,key) if key==  &MSG_KEY_CHAR_D => {
                     match self.view.get_state(COMPONENT_TREEVIEW) {
                         Some(Payload::One(Value::Str(node_id))) => {
@@ -429,6 +480,16 @@ impl TermusicActivity {
                     None
                 }
 
+                // full screen lyrics
+                (_,key) if key == &MSG_KEY_CHAR_CAPITAL_K => {
+                    self.toggle_lyric_fullscreen();
+                    None
+                }
+                // pair translation with lyrics
+                (_,key) if key == &MSG_KEY_CTRL_T => {
+                    self.switch_translation();
+                    None
+                }
                 // switch lyrics
                 (_,key) if key == &MSG_KEY_CHAR_CAPITAL_T => {
                     if let Some(mut song) = self.current_song.clone() {
@@ -441,15 +502,17 @@ impl TermusicActivity {
                         }
                         if let Some(f) = song.lyric_frames.get(song.lyric_selected) {
                             if let Ok(parsed_lyric) = Lyric::from_str(&f.text) {
-                                let tx = self.sender_message.clone();
                                 song.parsed_lyric = Some(parsed_lyric);
                                 let lang_ext = f.description.clone();
+                                let selected = song.lyric_selected;
                                 self.current_song = Some(song);
-                                thread::spawn(move || {
-                                    let _drop = tx.send(MessageState::Show(("Lyric switch successful".to_string(),format!("{} lyric is showing",lang_ext))));
-                                    sleep(Duration::from_secs(5));
-                                    let _drop = tx.send(MessageState::Hide);
-                                });
+                                // a lyric is not the translation of itself
+                                if self.lyric_translation.as_ref().map(|t| t.index) == Some(selected) {
+                                    self.lyric_translation = None;
+                                }
+                                self.align_translation();
+                                self.remember_lyric_pair();
+                                Self::show_message(&self.sender_message, "Lyric switch successful", &format!("{} lyric is showing", lang_ext));
                             }
                         }
                     }
@@ -473,6 +536,117 @@ impl TermusicActivity {
                     None
                 }
 
+                // equalizer
+                (COMPONENT_RADIO_EQUALIZER, Msg::OnSubmit(_)) => {
+                    if let Some(Payload::One(Value::Usize(index))) =
+                        self.view.get_state(COMPONENT_RADIO_EQUALIZER)
+                    {
+                        self.umount_equalizer();
+                        self.set_equalizer_preset(index);
+                    }
+                    None
+                }
+
+                (COMPONENT_RADIO_EQUALIZER,key) if (key==  &MSG_KEY_ESC) | (key == &MSG_KEY_CHAR_CAPITAL_Q) => {
+                    self.umount_equalizer();
+                    None
+                }
+
+                // playback rate
+                (_,key) if key==  &MSG_KEY_CHAR_RIGHT_BRACKET => {
+                    self.player.set_rate(self.player.rate() + 0.1);
+                    self.update_progress_title();
+                    None
+                }
+                (_,key) if key==  &MSG_KEY_CHAR_LEFT_BRACKET => {
+                    self.player.set_rate(self.player.rate() - 0.1);
+                    self.update_progress_title();
+                    None
+                }
+                (COMPONENT_TREEVIEW,key) if key==  &MSG_KEY_CHAR_V => {
+                    self.save_playback_rate();
+                    None
+                }
+
+                (_,key) if key==  &MSG_KEY_CHAR_E => {
+                    self.mount_equalizer();
+                    None
+                }
+
+                // sleep timer
+                (COMPONENT_RADIO_SLEEP_TIMER, Msg::OnSubmit(_)) => {
+                    if let Some(Payload::One(Value::Usize(index))) =
+                        self.view.get_state(COMPONENT_RADIO_SLEEP_TIMER)
+                    {
+                        self.umount_sleep_timer();
+                        self.set_sleep_timer(index);
+                    }
+                    None
+                }
+
+                (COMPONENT_RADIO_SLEEP_TIMER,key) if (key==  &MSG_KEY_ESC) || (key == &MSG_KEY_CHAR_CAPITAL_Q) => {
+                    self.umount_sleep_timer();
+                    None
+                }
+
+                // A-B loop
+                (_,key) if key==  &MSG_KEY_CHAR_X => {
+                    self.player.toggle_loop_a();
+                    let (new_prog, time_pos, duration) = self.player.get_progress();
+                    self.update_progress_bar(new_prog, time_pos, duration);
+                    None
+                }
+
+                (_,key) if key==  &MSG_KEY_CHAR_CAPITAL_X => {
+                    self.player.toggle_loop_b();
+                    let (new_prog, time_pos, duration) = self.player.get_progress();
+                    self.update_progress_bar(new_prog, time_pos, duration);
+                    None
+                }
+
+                // output device
+                (COMPONENT_TABLE_OUTPUT_DEVICE,key) if key== &MSG_KEY_ENTER => {
+                    if let Some(Payload::One(Value::Usize(index))) = self.view.get_state(COMPONENT_TABLE_OUTPUT_DEVICE) {
+                        self.set_output_device(index);
+                    }
+                    self.umount_output_device();
+                    None
+                }
+
+                (COMPONENT_TABLE_OUTPUT_DEVICE,key) if (key==  &MSG_KEY_ESC) || (key == &MSG_KEY_CHAR_CAPITAL_Q) => {
+                    self.umount_output_device();
+                    None
+                }
+
+                (_,key) if key==  &MSG_KEY_CHAR_O => {
+                    self.mount_output_device();
+                    None
+                }
+
+                // radio stations
+                (COMPONENT_TABLE_STATIONS,key) if key== &MSG_KEY_ENTER => {
+                    if let Some(Payload::One(Value::Usize(index))) = self.view.get_state(COMPONENT_TABLE_STATIONS) {
+                        self.add_station(index);
+                    }
+                    self.umount_stations();
+                    None
+                }
+
+                (COMPONENT_TABLE_STATIONS,key) if (key==  &MSG_KEY_ESC) || (key == &MSG_KEY_CHAR_CAPITAL_Q) => {
+                    self.umount_stations();
+                    None
+                }
+
+                (_,key) if key==  &MSG_KEY_CHAR_W => {
+                    self.mount_stations();
+                    None
+                }
+
+                (_,key) if key==  &MSG_KEY_CHAR_Z => {
+                    self.mount_sleep_timer();
+                    None
+                }
+
                 (_,key) if key==  &MSG_KEY_CTRL_H => {
                     // Show help
                     self.mount_help();
@@ -512,13 +686,25 @@ impl TermusicActivity {
 
     pub fn update_progress_title(&mut self) {
         if let Some(song) = &self.current_song {
-            let artist = song.artist().unwrap_or("Unknown Artist");
-            let title = song.title().unwrap_or("Unknown Title");
+            let mut artist = song.artist().unwrap_or("Unknown Artist");
+            let mut title = song.title().unwrap_or("Unknown Title");
+            // station name and the title it sends
+            if song.is_stream() {
+                if let Some(stream_title) = &self.stream_title {
+                    artist = title;
+                    title = stream_title;
+                }
+            }
+            let rate = if (self.player.rate() - 1.0).abs() < f64::EPSILON {
+                String::new()
+            } else {
+                format!(" ({:.1}x)", self.player.rate())
+            };
             if let Some(props) = self.view.get_props(COMPONENT_PROGRESS) {
                 let props = ProgressBarPropsBuilder::from(props)
                     // .with_progress(new_prog)
                     .with_title(
-                        format!("Playing: {} - {}", artist, title),
+                        format!("Playing: {} - {}{}", artist, title, rate),
                         Alignment::Center,
                     )
                     .build();
@@ -548,8 +734,18 @@ impl TermusicActivity {
             return;
         }
 
-        if time_pos >= duration {
-            self.status = Some(Status::Stopped);
+        // start crossfading to next song before the current one ends
+        let crossfade = self.player.crossfade_duration();
+        if crossfade > 0
+            && self
+                .auto_next()
+                .is_some_and(|next| self.should_crossfade(next))
+            && !self.player.has_next()
+            && !self.player.is_fading()
+            && duration > crossfade
+            && time_pos + crossfade >= duration
+        {
+            self.next_song_on_finish();
             return;
         }
 
@@ -558,22 +754,8 @@ impl TermusicActivity {
             None => return,
         };
 
-        if time_pos > self.time_pos || time_pos < 2 {
-            self.time_pos = time_pos;
-            if let Some(props) = self.view.get_props(COMPONENT_PROGRESS) {
-                let props = ProgressBarPropsBuilder::from(props)
-                    .with_progress(new_prog)
-                    .with_label(format!(
-                        "{}     :     {} ",
-                        format_duration(Duration::from_secs(time_pos as u64)),
-                        format_duration(Duration::from_secs(duration as u64))
-                    ))
-                    .build();
-                let msg = self.view.update(COMPONENT_PROGRESS, props);
-                self.redraw = true;
-                self.update(msg);
-            }
-        }
+        self.time_pos = time_pos;
+        self.update_progress_bar(new_prog, time_pos, duration);
 
         // Update lyrics
         if self.queue_items.is_empty() {
@@ -582,32 +764,100 @@ impl TermusicActivity {
 
         if song.lyric_frames.is_empty() {
             if let Some(props) = self.view.get_props(COMPONENT_PARAGRAPH_LYRIC) {
-                let props = ParagraphPropsBuilder::from(props)
-                    .with_texts(vec![TextSpan::new("No lyrics available.")])
+                let props = LyricPropsBuilder::from(props)
+                    .with_text(TextSpan::new("No lyrics available."))
                     .build();
                 self.view.update(COMPONENT_PARAGRAPH_LYRIC, props);
                 return;
             }
         }
 
-        let mut line = String::new();
+        let mut lines = vec![vec![TextSpan::new("")]];
+        let mut current = 0;
         if let Some(l) = song.parsed_lyric.as_ref() {
             if l.unsynced_captions.is_empty() {
                 return;
             }
-            if let Some(l) = l.get_text(time_pos) {
-                line = l;
+            let time_ms = self.player.position_ms();
+            current = l.get_index(time_ms).unwrap_or(0);
+            let words = l.get_words(time_ms);
+            lines = l
+                .unsynced_captions
+                .iter()
+                .enumerate()
+                .map(|(i, caption)| match &words {
+                    _ if i != current => vec![TextSpan::new(caption.text())],
+                    // enhanced lrc highlights the sung words, plain lrc the whole line
+                    Some((done, rest)) => vec![
+                        TextSpan::new(done).bold().fg(Color::Yellow),
+                        TextSpan::new(rest).bold(),
+                    ],
+                    None => vec![TextSpan::new(caption.text()).bold().fg(Color::Yellow)],
+                })
+                .collect();
+            // translation goes under the line it's aligned with
+            if let Some(translation) = self.lyric_translation.as_ref() {
+                let mut rows = vec![];
+                let mut current_row = 0;
+                for (i, (line, translated)) in lines.into_iter().zip(&translation.lines).enumerate()
+                {
+                    if i == current {
+                        current_row = rows.len();
+                    }
+                    rows.push(line);
+                    if let Some(text) = translated {
+                        let span = TextSpan::new(text).italic();
+                        rows.push(vec![if i == current {
+                            span.fg(Color::Yellow)
+                        } else {
+                            span
+                        }]);
+                    }
+                }
+                lines = rows;
+                current = current_row;
             }
         }
 
         if let Some(props) = self.view.get_props(COMPONENT_PARAGRAPH_LYRIC) {
-            let props = ParagraphPropsBuilder::from(props)
-                .with_texts(vec![TextSpan::new(line)])
+            let props = LyricPropsBuilder::from(props)
+                .with_lines(lines)
+                .with_current(current)
                 .build();
             self.view.update(COMPONENT_PARAGRAPH_LYRIC, props);
         }
     }
 
+    pub fn update_progress_bar(&mut self, new_prog: f64, time_pos: u64, duration: u64) {
+        let end = if self.current_song.as_ref().is_some_and(Song::is_stream) {
+            "live".to_string()
+        } else {
+            format_duration(Duration::from_secs(duration)).to_string()
+        };
+        let mut label = format!(
+            "{}     :     {} ",
+            format_duration(Duration::from_secs(time_pos)),
+            end
+        );
+        // markers of A-B loop
+        match self.player.ab_loop() {
+            (Some(a), Some(b)) => {
+                label = format!("{}    [A {} - B {}]", label, format_ms(a), format_ms(b));
+            }
+            (Some(a), None) => label = format!("{}    [A {} - B ?]", label, format_ms(a)),
+            _ => {}
+        }
+        if let Some(props) = self.view.get_props(COMPONENT_PROGRESS) {
+            let props = ProgressBarPropsBuilder::from(props)
+                .with_progress(new_prog)
+                .with_label(label)
+                .build();
+            let msg = self.view.update(COMPONENT_PROGRESS, props);
+            self.redraw = true;
+            self.update(msg);
+        }
+    }
+
     // update picture of album
     #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
     pub fn update_photo(&mut self) {
@@ -626,34 +876,68 @@ impl TermusicActivity {
             Some(c) => c.clear_image(),
             None => return,
         }
+        self.cover_shown = false;
 
-        // just show the first photo
-        if let Some(picture) = &song.picture {
+        // just show the front cover
+        if let Some(picture) = song.cover() {
             if let Ok(image) = image::load_from_memory(&picture.data) {
                 let (term_width, term_height) = viuer::terminal_size();
                 // Set desired image dimensions
                 let (orig_width, orig_height) = image::GenericImageView::dimensions(&image);
                 // let ratio = f64::from(orig_height) / f64::from(orig_width);
-                let width = 20_u16;
-                let height = (width * orig_height as u16).checked_div(orig_width as u16);
+                let width = if self.lyric_fullscreen {
+                    (term_width / 3).saturating_sub(4)
+                } else {
+                    20_u16
+                };
+                let height = (u32::from(width) * orig_height)
+                    .checked_div(orig_width)
+                    .and_then(|h| u16::try_from(h).ok());
                 if let Some(height) = height {
+                    let (x, y) = if self.lyric_fullscreen {
+                        // in the middle of left third, above progress bar and status line
+                        (2, term_height.saturating_sub(4 + height / 2) / 2)
+                    } else {
+                        (
+                            term_width - width - 1,
+                            term_height.saturating_sub(height / 2 + 12),
+                        )
+                    };
                     let config = viuer::Config {
                         transparent: true,
                         absolute_offset: true,
-                        x: term_width - width - 1,
-                        y: (term_height - height / 2 - 8) as i16 - 1,
+                        x,
+                        y: y as i16,
                         // x: term_width / 3 - width - 1,
                         // y: (term_height - height / 2) as i16 - 2,
                         width: Some(u32::from(width)),
                         height: None,
                         ..viuer::Config::default()
                     };
-                    let _drop = viuer::print(&image, &config);
+                    self.cover_shown = viuer::print(&image, &config).is_ok();
                 }
             }
         }
     }
 
+    // toggle_lyric_fullscreen switches between the main view and lyrics filling the screen
+    pub fn toggle_lyric_fullscreen(&mut self) {
+        self.lyric_fullscreen = !self.lyric_fullscreen;
+        let alignment = if self.lyric_fullscreen {
+            Alignment::Center
+        } else {
+            Alignment::Left
+        };
+        if let Some(props) = self.view.get_props(COMPONENT_PARAGRAPH_LYRIC) {
+            let props = LyricPropsBuilder::from(props)
+                .with_alignment(alignment)
+                .build();
+            self.view.update(COMPONENT_PARAGRAPH_LYRIC, props);
+        }
+        // album art moves with the layout
+        self.update_photo();
+    }
+
     // change status bar text to indicate the downloading state
     pub fn update_download_progress(&mut self) {
         if let Ok(transfer_state) = self.receiver.try_recv() {
@@ -687,7 +971,11 @@ impl TermusicActivity {
     pub fn update_status_line(&mut self, s: StatusLine) {
         match s {
             StatusLine::Default => {
-                let text = format!("Press <CTRL+H> for help. Version: {}", crate::VERSION);
+                let mut text = format!("Press <CTRL+H> for help. Version: {}", crate::VERSION);
+                self.sleep_timer_label = self.sleep_timer_label();
+                if let Some(label) = &self.sleep_timer_label {
+                    text = format!("{} | {}", text, label);
+                }
                 if let Some(props) = self.view.get_props(COMPONENT_LABEL_HELP) {
                     let props = LabelPropsBuilder::from(props)
                         .with_text(text)
@@ -783,32 +1071,124 @@ impl TermusicActivity {
         if let Ok(queue_items) = self.receiver_queueitems.try_recv() {
             self.queue_items = queue_items;
             self.sync_queue();
+            self.restore_session();
+            if self.loop_mode == LoopMode::Once && self.once_marker.is_none() {
+                // the marker couldn't be set before queue was loaded
+                self.set_loop_mode(LoopMode::Once);
+            }
             self.redraw = true;
         }
     }
 
+    // update player messages, like gapless switch to next song or position change
+    pub fn update_player_msg(&mut self) {
+        while let Some(msg) = self.player.next_msg() {
+            match msg {
+                PlayerMsg::CurrentTrackUpdated => self.next_song_gapless(),
+                PlayerMsg::Error(file, e) => self.skip_broken_song(file, &e),
+                PlayerMsg::Warning(w) => warn!("player warning: {}", w),
+                PlayerMsg::EndOfStream => {
+                    if matches!(self.status, Some(Status::Running)) {
+                        self.status = Some(Status::Stopped);
+                    }
+                }
+                PlayerMsg::PositionUpdated => self.update_progress(),
+                PlayerMsg::DurationChanged => self.update_duration(),
+                PlayerMsg::StreamTitle(title) => self.update_stream_title(title),
+                PlayerMsg::StateChanged(state) => match state {
+                    PlayerState::Playing => self.status = Some(Status::Running),
+                    // stopped is left to end of stream, which moves on to next song
+                    PlayerState::Paused if matches!(self.status, Some(Status::Running)) => {
+                        self.status = Some(Status::Paused);
+                    }
+                    _ => {}
+                },
+            }
+        }
+    }
+
+    // skip_broken_song marks the file that failed to play and moves on to the next song
+    fn skip_broken_song(&mut self, file: Option<String>, error: &str) {
+        let current = self.current_song.as_ref().and_then(Song::file);
+        let Some(file) = file.or_else(|| current.map(String::from)) else {
+            return;
+        };
+        if is_stream(&file) {
+            // streams may fail for a while only, they are not marked. The station is skipped
+            // once, later errors come after it's switched away.
+            if current != Some(file.as_str()) {
+                return;
+            }
+        } else if !self.broken_songs.insert(file.clone()) {
+            // gstreamer may post more than one error for the same file
+            return;
+        }
+        self.mount_error(&format!("Failed to play {}: {}", file, error));
+        // a station that fails alone in queue would be retried at once, it's paused instead
+        let others = self
+            .queue_items
+            .iter()
+            .any(|s| s.file() != Some(file.as_str()) && !self.is_broken(s));
+        if is_stream(&file) && !others {
+            self.status = Some(Status::Paused);
+            self.player.pause();
+            return;
+        }
+        self.next_song();
+    }
+
     pub fn update_playing_song(&self) {
         if let Some(song) = &self.current_song {
-            let name = song.name().unwrap_or("Unknown Song").to_string();
-            let tx = self.sender_message.clone();
-            thread::spawn(move || {
-                let _drop = tx.send(MessageState::Show(("Current Playing".to_string(), name)));
-                sleep(Duration::from_secs(5));
-                let _drop = tx.send(MessageState::Hide);
-            });
+            let name = song.name().unwrap_or("Unknown Song");
+            Self::show_message(&self.sender_message, "Current Playing", name);
         }
     }
 
     pub fn next_song(&mut self) {
+        self.switch_song(true);
+    }
+
+    // cue_next_song loads the next song without playing it, so that playback could stop at
+    // the end of current song and go on from there when resumed
+    pub fn cue_next_song(&mut self) {
+        self.status = Some(Status::Paused);
+        self.switch_song(false);
+    }
+
+    fn switch_song(&mut self, play: bool) {
         if self.queue_items.is_empty() {
             return;
         }
+        // skip broken songs, at most once around the queue
+        for _ in 0..self.queue_items.len() {
+            match self.queue_items.front() {
+                Some(song) if self.is_broken(song) => self.queue_items.rotate_left(1),
+                _ => break,
+            }
+        }
+        if self
+            .queue_items
+            .front()
+            .is_none_or(|song| self.is_broken(song))
+        {
+            return;
+        }
         if let Some(song) = self.queue_items.pop_front() {
             if let Some(file) = song.file() {
-                self.player.queue_and_play(file);
+                if !play {
+                    self.player.queue_and_pause(file);
+                } else if self.should_crossfade(&song) {
+                    self.player.crossfade_and_play(file);
+                } else {
+                    self.player.queue_and_play(file);
+                }
             }
+            self.player.apply_replaygain(song.replaygain());
+            self.apply_playback_rate(&song);
+            self.start_stream(&song);
             self.queue_items.push_back(song.clone());
             self.current_song = Some(song);
+            self.apply_lyric_pair();
             self.sync_queue();
             self.update_photo();
             self.update_progress_title();
@@ -817,6 +1197,54 @@ impl TermusicActivity {
         }
     }
 
+    // next_song_gapless rotates the queue when the player has already started the preloaded
+    // song by itself, so there is no need to call queue_and_play here.
+    pub fn next_song_gapless(&mut self) {
+        if self.loop_mode == LoopMode::RepeatOne {
+            // the same song was preloaded, it's at the back of queue
+            if let Some(song) = self.queue_items.pop_back() {
+                self.queue_items.push_front(song);
+            }
+        }
+        if let Some(song) = self.queue_items.pop_front() {
+            if let Some(file) = song.file() {
+                self.player.update_metadata(file);
+            }
+            self.player.clear_ab_loop();
+            self.player.apply_replaygain(song.replaygain());
+            self.apply_playback_rate(&song);
+            self.start_stream(&song);
+            self.time_pos = 0;
+            self.queue_items.push_back(song.clone());
+            self.current_song = Some(song);
+            self.apply_lyric_pair();
+            self.sync_queue();
+            self.update_photo();
+            self.update_progress_title();
+            self.update_duration();
+            self.update_playing_song();
+        }
+    }
+
+    // should_crossfade tells whether switching from current song to next should crossfade.
+    // Songs of the same album are played gapless instead, to keep albums intact.
+    pub fn should_crossfade(&self, next: &Song) -> bool {
+        if self.player.crossfade_duration() == 0 {
+            return false;
+        }
+        if let Some(current) = &self.current_song {
+            if current.file() == next.file() {
+                return false;
+            }
+            if let (Some(a), Some(b)) = (current.album(), next.album()) {
+                if a == b {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
     pub fn previous_song(&mut self) {
         if self.queue_items.is_empty() {
             return;
@@ -830,3 +1258,8 @@ impl TermusicActivity {
         self.next_song();
     }
 }
+
+// format_ms formats milliseconds as m:ss.mmm
+fn format_ms(ms: u64) -> String {
+    format!("{}:{:02}.{:03}", ms / 60_000, ms / 1000 % 60, ms % 1000)
+}
@@ -23,12 +23,17 @@
  */
 // Locals
 use super::{
-    TermusicActivity, COMPONENT_CONFIRMATION_INPUT, COMPONENT_CONFIRMATION_RADIO,
-    COMPONENT_INPUT_URL, COMPONENT_LABEL_HELP, COMPONENT_PARAGRAPH_LYRIC, COMPONENT_PROGRESS,
-    COMPONENT_TABLE_QUEUE, COMPONENT_TABLE_YOUTUBE, COMPONENT_TEXT_ERROR, COMPONENT_TEXT_HELP,
+    sleep_timer::SLEEP_TIMER_OPTIONS, MessageState, TermusicActivity, COMPONENT_CONFIRMATION_INPUT,
+    COMPONENT_CONFIRMATION_RADIO, COMPONENT_INPUT_URL, COMPONENT_LABEL_HELP,
+    COMPONENT_PARAGRAPH_LYRIC, COMPONENT_PROGRESS, COMPONENT_RADIO_EQUALIZER,
+    COMPONENT_RADIO_SLEEP_TIMER, COMPONENT_TABLE_OUTPUT_DEVICE, COMPONENT_TABLE_QUEUE,
+    COMPONENT_TABLE_STATIONS, COMPONENT_TABLE_YOUTUBE, COMPONENT_TEXT_ERROR, COMPONENT_TEXT_HELP,
     COMPONENT_TEXT_MESSAGE, COMPONENT_TREEVIEW,
 };
-use crate::ui::{draw_area_in, draw_area_top_right};
+use crate::ui::{
+    components::lyric::{LyricParagraph, LyricPropsBuilder},
+    draw_area_in, draw_area_top_right,
+};
 // Ext
 use tui_realm_stdlib::{
     Input, InputPropsBuilder, Label, LabelPropsBuilder, Paragraph, ParagraphPropsBuilder,
@@ -45,10 +50,14 @@ use tuirealm::{
         style::Color,
         widgets::Clear,
     },
-    PropsBuilder, View,
+    Frame, PropsBuilder, View,
 };
 // tui
 use tui_realm_treeview::{TreeView, TreeViewPropsBuilder};
+// std
+use std::sync::mpsc::Sender;
+use std::thread::{self, sleep};
+use std::time::Duration;
 
 impl TermusicActivity {
     // -- view
@@ -87,14 +96,16 @@ impl TermusicActivity {
         );
         self.view.mount(
             COMPONENT_PARAGRAPH_LYRIC,
-            Box::new(Paragraph::new(
-                ParagraphPropsBuilder::default()
+            Box::new(LyricParagraph::new(
+                LyricPropsBuilder::default()
                     .with_foreground(Color::Cyan)
                     .with_borders(Borders::ALL, BorderType::Rounded, Color::Green)
                     .with_title("Lyrics", Alignment::Left)
-                    .with_texts(vec![TextSpan::new("No Lyrics available.")
-                        .underlined()
-                        .fg(Color::Green)])
+                    .with_text(
+                        TextSpan::new("No Lyrics available.")
+                            .underlined()
+                            .fg(Color::Green),
+                    )
                     .build(),
             )),
         );
@@ -147,36 +158,40 @@ impl TermusicActivity {
     pub(super) fn view(&mut self) {
         if let Some(mut ctx) = self.context.take() {
             let _drop = ctx.context.draw(|f| {
-                // Prepare chunks
-                let chunks_main = Layout::default()
-                    .direction(Direction::Vertical)
-                    .margin(0)
-                    .constraints([Constraint::Min(2), Constraint::Length(1)].as_ref())
-                    .split(f.size());
-                let chunks_left = Layout::default()
-                    .direction(Direction::Horizontal)
-                    .margin(0)
-                    .constraints([Constraint::Ratio(1, 3), Constraint::Ratio(2, 3)].as_ref())
-                    .split(chunks_main[0]);
-                let chunks_right = Layout::default()
-                    .direction(Direction::Vertical)
-                    .margin(0)
-                    .constraints(
-                        [
-                            Constraint::Min(2),
-                            Constraint::Length(3),
-                            Constraint::Length(4),
-                        ]
-                        .as_ref(),
-                    )
-                    .split(chunks_left[1]);
+                if self.lyric_fullscreen {
+                    self.render_lyric_fullscreen(f);
+                } else {
+                    // Prepare chunks
+                    let chunks_main = Layout::default()
+                        .direction(Direction::Vertical)
+                        .margin(0)
+                        .constraints([Constraint::Min(2), Constraint::Length(1)].as_ref())
+                        .split(f.size());
+                    let chunks_left = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .margin(0)
+                        .constraints([Constraint::Ratio(1, 3), Constraint::Ratio(2, 3)].as_ref())
+                        .split(chunks_main[0]);
+                    let chunks_right = Layout::default()
+                        .direction(Direction::Vertical)
+                        .margin(0)
+                        .constraints(
+                            [
+                                Constraint::Min(2),
+                                Constraint::Length(3),
+                                Constraint::Length(7),
+                            ]
+                            .as_ref(),
+                        )
+                        .split(chunks_left[1]);
 
-                self.view.render(COMPONENT_TREEVIEW, f, chunks_left[0]);
-                self.view.render(COMPONENT_LABEL_HELP, f, chunks_main[1]);
-                self.view.render(COMPONENT_TABLE_QUEUE, f, chunks_right[0]);
-                self.view.render(COMPONENT_PROGRESS, f, chunks_right[1]);
-                self.view
-                    .render(COMPONENT_PARAGRAPH_LYRIC, f, chunks_right[2]);
+                    self.view.render(COMPONENT_TREEVIEW, f, chunks_left[0]);
+                    self.view.render(COMPONENT_LABEL_HELP, f, chunks_main[1]);
+                    self.view.render(COMPONENT_TABLE_QUEUE, f, chunks_right[0]);
+                    self.view.render(COMPONENT_PROGRESS, f, chunks_right[1]);
+                    self.view
+                        .render(COMPONENT_PARAGRAPH_LYRIC, f, chunks_right[2]);
+                }
 
                 if let Some(props) = self.view.get_props(COMPONENT_TEXT_HELP) {
                     if props.visible {
@@ -232,6 +247,8 @@ impl TermusicActivity {
                     }
                 }
 
+                self.render_player_popups(f);
+
                 if let Some(props) = self.view.get_props(COMPONENT_TABLE_YOUTUBE) {
                     if props.visible {
                         let popup = draw_area_in(f.size(), 66, 60);
@@ -245,6 +262,74 @@ impl TermusicActivity {
         }
     }
 
+    // render_lyric_fullscreen shows lyric over the progress bar, album art is printed by kitty
+    // on the left third
+    fn render_lyric_fullscreen(&self, f: &mut Frame) {
+        let chunks_main = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(0)
+            .constraints(
+                [
+                    Constraint::Min(2),
+                    Constraint::Length(3),
+                    Constraint::Length(1),
+                ]
+                .as_ref(),
+            )
+            .split(f.size());
+        let chunk_lyric = if self.cover_shown {
+            Layout::default()
+                .direction(Direction::Horizontal)
+                .margin(0)
+                .constraints([Constraint::Ratio(1, 3), Constraint::Ratio(2, 3)].as_ref())
+                .split(chunks_main[0])[1]
+        } else {
+            chunks_main[0]
+        };
+        self.view.render(COMPONENT_PARAGRAPH_LYRIC, f, chunk_lyric);
+        self.view.render(COMPONENT_PROGRESS, f, chunks_main[1]);
+        self.view.render(COMPONENT_LABEL_HELP, f, chunks_main[2]);
+    }
+
+    // render_player_popups draws the popups of player settings and radio stations
+    fn render_player_popups(&self, f: &mut Frame) {
+        if let Some(props) = self.view.get_props(COMPONENT_RADIO_EQUALIZER) {
+            if props.visible {
+                let popup = draw_area_in(f.size(), 50, 10);
+                f.render_widget(Clear, popup);
+                // make popup
+                self.view.render(COMPONENT_RADIO_EQUALIZER, f, popup);
+            }
+        }
+
+        if let Some(props) = self.view.get_props(COMPONENT_RADIO_SLEEP_TIMER) {
+            if props.visible {
+                let popup = draw_area_in(f.size(), 76, 10);
+                f.render_widget(Clear, popup);
+                // make popup
+                self.view.render(COMPONENT_RADIO_SLEEP_TIMER, f, popup);
+            }
+        }
+
+        if let Some(props) = self.view.get_props(COMPONENT_TABLE_OUTPUT_DEVICE) {
+            if props.visible {
+                let popup = draw_area_in(f.size(), 60, 40);
+                f.render_widget(Clear, popup);
+                // make popup
+                self.view.render(COMPONENT_TABLE_OUTPUT_DEVICE, f, popup);
+            }
+        }
+
+        if let Some(props) = self.view.get_props(COMPONENT_TABLE_STATIONS) {
+            if props.visible {
+                let popup = draw_area_in(f.size(), 66, 50);
+                f.render_widget(Clear, popup);
+                // make popup
+                self.view.render(COMPONENT_TABLE_STATIONS, f, popup);
+            }
+        }
+    }
+
     // -- mount
 
     // ### mount_error
@@ -296,6 +381,18 @@ impl TermusicActivity {
         // self.view.active(COMPONENT_TEXT_MESSAGE);
     }
 
+    // show_message shows the message box for 5 seconds. It only needs the sender, so that it
+    // could be called from other threads as well.
+    pub(super) fn show_message(tx: &Sender<MessageState>, title: &str, text: &str) {
+        let tx = tx.clone();
+        let message = (title.to_string(), text.to_string());
+        thread::spawn(move || {
+            let _drop = tx.send(MessageState::Show(message));
+            sleep(Duration::from_secs(5));
+            let _drop = tx.send(MessageState::Hide);
+        });
+    }
+
     /// ### `umount_message`
     ///
     /// Umount error message
@@ -374,10 +471,156 @@ impl TermusicActivity {
         self.view.umount(COMPONENT_INPUT_URL);
     }
 
+    /// ### `mount_equalizer`
+    ///
+    /// Mount equalizer preset selector
+    pub(super) fn mount_equalizer(&mut self) {
+        let presets: Vec<String> = self
+            .config
+            .equalizer_presets
+            .iter()
+            .map(|p| p.name.clone())
+            .collect();
+        let index = presets
+            .iter()
+            .position(|name| name == &self.config.equalizer)
+            .unwrap_or(0);
+        self.view.mount(
+            COMPONENT_RADIO_EQUALIZER,
+            Box::new(Radio::new(
+                RadioPropsBuilder::default()
+                    .with_color(Color::LightGreen)
+                    .with_inverted_color(Color::Black)
+                    .with_borders(Borders::ALL, BorderType::Rounded, Color::LightGreen)
+                    .with_title("Equalizer", Alignment::Left)
+                    .with_options(&presets)
+                    .with_value(index)
+                    .build(),
+            )),
+        );
+        self.view.active(COMPONENT_RADIO_EQUALIZER);
+    }
+
+    /// ### `umount_equalizer`
+    ///
+    /// Umount equalizer preset selector
+    pub(super) fn umount_equalizer(&mut self) {
+        self.view.umount(COMPONENT_RADIO_EQUALIZER);
+    }
+
+    /// ### `mount_sleep_timer`
+    ///
+    /// Mount sleep timer selector
+    pub(super) fn mount_sleep_timer(&mut self) {
+        self.view.mount(
+            COMPONENT_RADIO_SLEEP_TIMER,
+            Box::new(Radio::new(
+                RadioPropsBuilder::default()
+                    .with_color(Color::LightGreen)
+                    .with_inverted_color(Color::Black)
+                    .with_borders(Borders::ALL, BorderType::Rounded, Color::LightGreen)
+                    .with_title("Sleep timer", Alignment::Left)
+                    .with_options(&SLEEP_TIMER_OPTIONS)
+                    .build(),
+            )),
+        );
+        self.view.active(COMPONENT_RADIO_SLEEP_TIMER);
+    }
+
+    /// ### `umount_sleep_timer`
+    ///
+    /// Umount sleep timer selector
+    pub(super) fn umount_sleep_timer(&mut self) {
+        self.view.umount(COMPONENT_RADIO_SLEEP_TIMER);
+    }
+
+    /// ### `mount_output_device`
+    ///
+    /// Mount output device selector
+    pub(super) fn mount_output_device(&mut self) {
+        self.output_devices = self.player.output_devices();
+        let mut table = TableBuilder::default();
+        table.add_col(TextSpan::from("Default"));
+        for device in &self.output_devices {
+            table.add_row().add_col(TextSpan::from(device.as_str()));
+        }
+        self.view.mount(
+            COMPONENT_TABLE_OUTPUT_DEVICE,
+            Box::new(Table::new(
+                TablePropsBuilder::default()
+                    .with_background(Color::Black)
+                    .with_highlighted_str(Some("\u{1f50a}"))
+                    .with_highlighted_color(Color::LightBlue)
+                    .with_max_scroll_step(4)
+                    .with_borders(Borders::ALL, BorderType::Rounded, Color::Blue)
+                    .with_title("Output device", Alignment::Left)
+                    .scrollable(true)
+                    .with_widths(&[100])
+                    .with_table(table.build())
+                    .build(),
+            )),
+        );
+        self.view.active(COMPONENT_TABLE_OUTPUT_DEVICE);
+    }
+
+    /// ### `umount_output_device`
+    ///
+    /// Umount output device selector
+    pub(super) fn umount_output_device(&mut self) {
+        self.view.umount(COMPONENT_TABLE_OUTPUT_DEVICE);
+    }
+
+    /// ### `mount_stations`
+    ///
+    /// Mount radio station list
+    pub(super) fn mount_stations(&mut self) {
+        let mut table = TableBuilder::default();
+        for (idx, station) in self.stations.stations.iter().enumerate() {
+            if idx > 0 {
+                table.add_row();
+            }
+            table
+                .add_col(TextSpan::new(station.name.as_str()).bold())
+                .add_col(TextSpan::from(station.url.as_str()));
+        }
+        if self.stations.stations.is_empty() {
+            table.add_col(TextSpan::from("No stations, add them to stations.toml"));
+            table.add_col(TextSpan::from(""));
+        }
+        self.view.mount(
+            COMPONENT_TABLE_STATIONS,
+            Box::new(Table::new(
+                TablePropsBuilder::default()
+                    .with_background(Color::Black)
+                    .with_highlighted_str(Some("\u{1f4fb}"))
+                    .with_highlighted_color(Color::LightBlue)
+                    .with_max_scroll_step(4)
+                    .with_borders(Borders::ALL, BorderType::Rounded, Color::Blue)
+                    .with_title("Radio stations", Alignment::Left)
+                    .scrollable(true)
+                    .with_widths(&[40, 60])
+                    .with_table(table.build())
+                    .build(),
+            )),
+        );
+        self.view.active(COMPONENT_TABLE_STATIONS);
+    }
+
+    /// ### `umount_stations`
+    ///
+    /// Umount radio station list
+    pub(super) fn umount_stations(&mut self) {
+        self.view.umount(COMPONENT_TABLE_STATIONS);
+    }
+
     // /// ### mount_help
     // ///
     // /// Mount help
     pub(super) fn mount_help(&mut self) {
+        let mut table = TableBuilder::default();
+        Self::help_general(&mut table);
+        Self::help_playlist(&mut table);
+        Self::help_queue(&mut table);
         self.view.mount(
             COMPONENT_TEXT_HELP,
             Box::new(Table::new(
@@ -386,64 +629,7 @@ impl TermusicActivity {
                     .with_title("Help", Alignment::Center)
                     .with_header(&["Key", "Function"])
                     .with_widths(&[30, 70])
-                    .with_table(
-                        TableBuilder::default()
-                            .add_col(TextSpan::new("<ESC> or <Q>").bold().fg(Color::Cyan))
-                            .add_col(TextSpan::from("Exit"))
-                            .add_row()
-                            .add_col(TextSpan::new("<TAB>").bold().fg(Color::Cyan))
-                            .add_col(TextSpan::from("Switch focus"))
-                            .add_row()
-                            .add_col(TextSpan::new("<h,j,k,l,g,G>").bold().fg(Color::Cyan))
-                            .add_col(TextSpan::from("Move cursor(vim style)"))
-                            .add_row()
-                            .add_col(TextSpan::new("<f/b>").bold().fg(Color::Cyan))
-                            .add_col(TextSpan::from("Seek forward/backward 5 seconds"))
-                            .add_row()
-                            .add_col(TextSpan::new("<F/B>").bold().fg(Color::Cyan))
-                            .add_col(TextSpan::from("Seek forward/backward 1 second for lyrics"))
-                            .add_row()
-                            .add_col(TextSpan::new("<F/B>").bold().fg(Color::Cyan))
-                            .add_col(TextSpan::from("Before 10 seconds,adjust offset of lyrics"))
-                            .add_row()
-                            .add_col(TextSpan::new("<T>").bold().fg(Color::Cyan))
-                            .add_col(TextSpan::from("Switch lyrics if more than 1 available"))
-                            .add_row()
-                            .add_col(TextSpan::new("<n/N/space>").bold().fg(Color::Cyan))
-                            .add_col(TextSpan::from("Next/Previous/Pause current song"))
-                            .add_row()
-                            .add_col(TextSpan::new("<+,=/-,_>").bold().fg(Color::Cyan))
-                            .add_col(TextSpan::from("Increase/Decrease volume"))
-                            .add_row()
-                            .add_col(TextSpan::new("Playlist").bold().fg(Color::LightYellow))
-                            .add_row()
-                            .add_col(TextSpan::new("<l/L>").bold().fg(Color::Cyan))
-                            .add_col(TextSpan::from("Add one/all songs to queue"))
-                            .add_row()
-                            .add_col(TextSpan::new("<d>").bold().fg(Color::Cyan))
-                            .add_col(TextSpan::from("Delete song or folder"))
-                            .add_row()
-                            .add_col(TextSpan::new("<s>").bold().fg(Color::Cyan))
-                            .add_col(TextSpan::from("Download or search song from youtube"))
-                            .add_row()
-                            .add_col(TextSpan::new("<t>").bold().fg(Color::Cyan))
-                            .add_col(TextSpan::from("Open tag editor for tag and lyric download"))
-                            .add_row()
-                            .add_col(TextSpan::new("<y/p>").bold().fg(Color::Cyan))
-                            .add_col(TextSpan::from("Yank and Paste files"))
-                            .add_row()
-                            .add_col(TextSpan::new("Queue").bold().fg(Color::LightYellow))
-                            .add_row()
-                            .add_col(TextSpan::new("<d/D>").bold().fg(Color::Cyan))
-                            .add_col(TextSpan::from("Delete one/all songs from queue"))
-                            .add_row()
-                            .add_col(TextSpan::new("<l>").bold().fg(Color::Cyan))
-                            .add_col(TextSpan::from("Play selected"))
-                            .add_row()
-                            .add_col(TextSpan::new("<s>").bold().fg(Color::Cyan))
-                            .add_col(TextSpan::from("Shuffle queue"))
-                            .build(),
-                    )
+                    .with_table(table.build())
                     .build(),
             )),
         );
@@ -451,6 +637,116 @@ impl TermusicActivity {
         self.view.active(COMPONENT_TEXT_HELP);
     }
 
+    // help_general lists the keys working everywhere
+    fn help_general(table: &mut TableBuilder) {
+        table
+            .add_col(TextSpan::new("<ESC> or <Q>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Exit"))
+            .add_row()
+            .add_col(TextSpan::new("<TAB>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Switch focus"))
+            .add_row()
+            .add_col(TextSpan::new("<h,j,k,l,g,G>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Move cursor(vim style)"))
+            .add_row()
+            .add_col(TextSpan::new("<f/b>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Seek forward/backward 5 seconds"))
+            .add_row()
+            .add_col(TextSpan::new("<F/B>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Seek forward/backward 1 second for lyrics"))
+            .add_row()
+            .add_col(TextSpan::new("<F/B>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Before 10 seconds,adjust offset of lyrics"))
+            .add_row()
+            .add_col(TextSpan::new("<T>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Switch lyrics if more than 1 available"))
+            .add_row()
+            .add_col(TextSpan::new("<CTRL+T>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Show another lyric as translation"))
+            .add_row()
+            .add_col(TextSpan::new("<K>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Toggle full screen lyrics with album art"))
+            .add_row()
+            .add_col(TextSpan::new("<n/N/space>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Next/Previous/Pause current song"))
+            .add_row()
+            .add_col(TextSpan::new("<m>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Switch repeat all/repeat one/play once"))
+            .add_row()
+            .add_col(TextSpan::new("<M>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Stop after current song"))
+            .add_row()
+            .add_col(TextSpan::new("<+,=/-,_>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Increase/Decrease volume"))
+            .add_row()
+            .add_col(TextSpan::new("<[/]>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Decrease/Increase playback rate"))
+            .add_row()
+            .add_col(TextSpan::new("<e>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Choose equalizer preset"))
+            .add_row()
+            .add_col(TextSpan::new("<x/X>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Mark A/B of loop, mark again to clear"))
+            .add_row()
+            .add_col(TextSpan::new("<z>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Set sleep timer"))
+            .add_row()
+            .add_col(TextSpan::new("<o>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Choose output device"))
+            .add_row()
+            .add_col(TextSpan::new("<w>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Add radio station to queue"));
+    }
+
+    // help_playlist lists the keys of playlist
+    fn help_playlist(table: &mut TableBuilder) {
+        table
+            .add_row()
+            .add_col(TextSpan::new("Playlist").bold().fg(Color::LightYellow))
+            .add_row()
+            .add_col(TextSpan::new("<l/L>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Add one/all songs to queue"))
+            .add_row()
+            .add_col(TextSpan::new("<d>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Delete song or folder"))
+            .add_row()
+            .add_col(TextSpan::new("<s>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Download or search song from youtube"))
+            .add_row()
+            .add_col(TextSpan::new("<t>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Open tag editor for tag and lyric download"))
+            .add_row()
+            .add_col(TextSpan::new("<y/p>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Yank and Paste files"))
+            .add_row()
+            .add_col(TextSpan::new("<v>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Save playback rate for song or folder"))
+            .add_row()
+            .add_col(TextSpan::new("<a/A>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Analyze replaygain of untagged/all songs"))
+            .add_row()
+            .add_col(TextSpan::new("<C>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from(
+                "Embed folder image into all songs of folder",
+            ));
+    }
+
+    // help_queue lists the keys of queue
+    fn help_queue(table: &mut TableBuilder) {
+        table
+            .add_row()
+            .add_col(TextSpan::new("Queue").bold().fg(Color::LightYellow))
+            .add_row()
+            .add_col(TextSpan::new("<d/D>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Delete one/all songs from queue"))
+            .add_row()
+            .add_col(TextSpan::new("<l>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Play selected"))
+            .add_row()
+            .add_col(TextSpan::new("<s>").bold().fg(Color::Cyan))
+            .add_col(TextSpan::from("Shuffle queue"));
+    }
+
     /// ### `umount_help`
     ///
     /// Umount help
@@ -23,12 +23,14 @@
  */
 use super::{TermusicActivity, TransferState, COMPONENT_TABLE_YOUTUBE, COMPONENT_TREEVIEW};
 use crate::invidious::{Instance, YoutubeVideo};
+use crate::song::Song;
 use anyhow::{anyhow, bail, Result};
 use humantime::format_duration;
 use id3::frame::Lyrics;
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::thread::{self, sleep};
 use std::time::Duration;
 use tui_realm_stdlib::TablePropsBuilder;
@@ -237,48 +239,37 @@ impl TermusicActivity {
                     if let Ok(file_fullname) =
                         extract_filepath(download.output(), &path.to_string_lossy())
                     {
-                        let mut id3_tag = if let Ok(tag) = id3::Tag::read_from_path(&file_fullname)
-                        {
-                            tag
-                        } else {
-                            let mut t = id3::Tag::new();
-                            let p: &Path = Path::new(&file_fullname);
-                            if let Some(p_base) = p.file_stem() {
-                                t.set_title(p_base.to_string_lossy());
-                            }
-                            let _drop = t.write_to_path(p, id3::Version::Id3v24);
-                            t
-                        };
-
-                        // here we add all downloaded lrc file
-                        if let Ok(files) = std::fs::read_dir(&path) {
-                            for f in files.flatten() {
-                                let name = f.file_name().clone();
-                                let p = Path::new(&name);
-                                if let Some(ext) = p.extension() {
-                                    if ext == "lrc" {
-                                        let mut lang_ext = "eng".to_string();
-                                        if let Some(p_short) = p.file_stem() {
-                                            let p2 = Path::new(p_short);
-                                            if let Some(ext2) = p2.extension() {
-                                                lang_ext = ext2.to_string_lossy().to_string();
+                        if let Ok(mut song) = Song::from_str(&file_fullname) {
+                            // here we add all downloaded lrc file
+                            if let Ok(files) = std::fs::read_dir(&path) {
+                                for f in files.flatten() {
+                                    let name = f.file_name().clone();
+                                    let p = Path::new(&name);
+                                    if let Some(ext) = p.extension() {
+                                        if ext == "lrc" {
+                                            let mut lang_ext = "eng".to_string();
+                                            if let Some(p_short) = p.file_stem() {
+                                                let p2 = Path::new(p_short);
+                                                if let Some(ext2) = p2.extension() {
+                                                    lang_ext = ext2.to_string_lossy().to_string();
+                                                }
                                             }
+                                            let lyric_string = std::fs::read_to_string(f.path());
+                                            song.lyric_frames.push(Lyrics {
+                                                lang: "eng".to_string(),
+                                                description: lang_ext,
+                                                text: lyric_string.unwrap_or_else(|_| {
+                                                    String::from("[00:00:01] No lyric")
+                                                }),
+                                            });
+                                            let _drop = std::fs::remove_file(f.path());
                                         }
-                                        let lyric_string = std::fs::read_to_string(f.path());
-                                        id3_tag.add_lyrics(Lyrics {
-                                            lang: "eng".to_string(),
-                                            description: lang_ext,
-                                            text: lyric_string.unwrap_or_else(|_| {
-                                                String::from("[00:00:01] No lyric")
-                                            }),
-                                        });
-                                        let _drop = std::fs::remove_file(f.path());
                                     }
                                 }
                             }
-                        }
 
-                        let _drop = id3_tag.write_to_path(&file_fullname, id3::Version::Id3v24);
+                            let _drop = song.write_tag();
+                        }
 
                         let _drop = tx.send(TransferState::Success);
                         sleep(Duration::from_secs(5));
@@ -50,11 +50,20 @@ const COMPONENT_TE_TEXT_HELP: &str = "TEXT_TE_HELP";
 const COMPONENT_TE_TEXT_ERROR: &str = "TEXT_TE_ERROR";
 const COMPONENT_TE_INPUT_ARTIST: &str = "INPUT_TE_ARTIST";
 const COMPONENT_TE_INPUT_SONGNAME: &str = "INPUT_TE_SONGNAME";
+const COMPONENT_TE_INPUT_ALBUM_ARTIST: &str = "INPUT_TE_ALBUM_ARTIST";
+const COMPONENT_TE_INPUT_COMPOSER: &str = "INPUT_TE_COMPOSER";
+const COMPONENT_TE_INPUT_GENRE: &str = "INPUT_TE_GENRE";
+const COMPONENT_TE_INPUT_YEAR: &str = "INPUT_TE_YEAR";
+const COMPONENT_TE_INPUT_TRACK: &str = "INPUT_TE_TRACK";
+const COMPONENT_TE_INPUT_DISC: &str = "INPUT_TE_DISC";
+const COMPONENT_TE_INPUT_COMMENT: &str = "INPUT_TE_COMMENT";
 const COMPONENT_TE_RADIO_TAG: &str = "RADIO_TE_TAG";
 const COMPONENT_TE_SCROLLTABLE_OPTIONS: &str = "SCROLLTABLE_TE_OPTIONS";
 const COMPONENT_TE_TEXTAREA_LYRIC: &str = "TEXTAREA_TE_LYRIC";
 const COMPONENT_TE_SELECT_LYRIC: &str = "SELECT_TE_LYRIC";
 const COMPONENT_TE_DELETE_LYRIC: &str = "DELETE_TE_LYRIC";
+const COMPONENT_TE_TABLE_PICTURES: &str = "TABLE_TE_PICTURES";
+const COMPONENT_TE_INPUT_COVER: &str = "INPUT_TE_COVER";
 
 /// ### `ViewLayout`
 ///
@@ -73,6 +82,9 @@ pub struct TagEditorActivity {
     receiver: Receiver<TransferState>,
     sender_songtag: Sender<SearchLyricState>,
     receiver_songtag: Receiver<SearchLyricState>,
+    // cover images larger than this are scaled down, 0 keeps them as they are
+    cover_max_size: u32,
+    cover_quality: u8,
 }
 
 pub enum SearchLyricState {
@@ -100,12 +112,19 @@ impl Default for TagEditorActivity {
             receiver: rx,
             sender_songtag: tx2,
             receiver_songtag: rx2,
+            cover_max_size: 0,
+            cover_quality: 85,
         }
     }
 }
 
 impl TagEditorActivity {
     // pub fn run(&mut self) {}
+
+    pub const fn set_cover_options(&mut self, max_size: u32, quality: u8) {
+        self.cover_max_size = max_size;
+        self.cover_quality = quality;
+    }
 }
 
 impl Activity for TagEditorActivity {
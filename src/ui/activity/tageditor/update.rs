@@ -24,21 +24,24 @@
 // locals
 use super::{
     ExitReason, SearchLyricState, TagEditorActivity, COMPONENT_TE_DELETE_LYRIC,
-    COMPONENT_TE_INPUT_ARTIST, COMPONENT_TE_INPUT_SONGNAME, COMPONENT_TE_LABEL_HELP,
-    COMPONENT_TE_RADIO_TAG, COMPONENT_TE_SCROLLTABLE_OPTIONS, COMPONENT_TE_SELECT_LYRIC,
+    COMPONENT_TE_INPUT_ALBUM_ARTIST, COMPONENT_TE_INPUT_ARTIST, COMPONENT_TE_INPUT_COMMENT,
+    COMPONENT_TE_INPUT_COMPOSER, COMPONENT_TE_INPUT_COVER, COMPONENT_TE_INPUT_DISC,
+    COMPONENT_TE_INPUT_GENRE, COMPONENT_TE_INPUT_SONGNAME, COMPONENT_TE_INPUT_TRACK,
+    COMPONENT_TE_INPUT_YEAR, COMPONENT_TE_LABEL_HELP, COMPONENT_TE_RADIO_TAG,
+    COMPONENT_TE_SCROLLTABLE_OPTIONS, COMPONENT_TE_SELECT_LYRIC, COMPONENT_TE_TABLE_PICTURES,
     COMPONENT_TE_TEXTAREA_LYRIC, COMPONENT_TE_TEXT_ERROR, COMPONENT_TE_TEXT_HELP,
 };
 use crate::ui::keymap::{
-    MSG_KEY_CHAR_CAPITAL_G, MSG_KEY_CHAR_CAPITAL_Q, MSG_KEY_CHAR_G, MSG_KEY_CHAR_H, MSG_KEY_CHAR_J,
-    MSG_KEY_CHAR_K, MSG_KEY_CHAR_L, MSG_KEY_CHAR_S, MSG_KEY_CTRL_H, MSG_KEY_ENTER, MSG_KEY_ESC,
-    MSG_KEY_TAB,
+    MSG_KEY_CHAR_CAPITAL_G, MSG_KEY_CHAR_CAPITAL_Q, MSG_KEY_CHAR_D, MSG_KEY_CHAR_G, MSG_KEY_CHAR_H,
+    MSG_KEY_CHAR_J, MSG_KEY_CHAR_K, MSG_KEY_CHAR_L, MSG_KEY_CHAR_S, MSG_KEY_CHAR_T, MSG_KEY_CTRL_H,
+    MSG_KEY_ENTER, MSG_KEY_ESC, MSG_KEY_TAB,
 };
 use crate::{
-    song::Song,
+    song::{find_folder_cover, load_cover, Song},
     songtag::search,
     ui::activity::main::{StatusLine, TransferState},
 };
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use tui_realm_stdlib::LabelPropsBuilder;
 use tuirealm::{
@@ -72,6 +75,34 @@ impl TagEditorActivity {
                 }
 
                 (COMPONENT_TE_RADIO_TAG, key) if key == &MSG_KEY_TAB => {
+                    self.view.active(COMPONENT_TE_INPUT_ALBUM_ARTIST);
+                    None
+                }
+                (COMPONENT_TE_INPUT_ALBUM_ARTIST, key) if key == &MSG_KEY_TAB => {
+                    self.view.active(COMPONENT_TE_INPUT_COMPOSER);
+                    None
+                }
+                (COMPONENT_TE_INPUT_COMPOSER, key) if key == &MSG_KEY_TAB => {
+                    self.view.active(COMPONENT_TE_INPUT_GENRE);
+                    None
+                }
+                (COMPONENT_TE_INPUT_GENRE, key) if key == &MSG_KEY_TAB => {
+                    self.view.active(COMPONENT_TE_INPUT_YEAR);
+                    None
+                }
+                (COMPONENT_TE_INPUT_YEAR, key) if key == &MSG_KEY_TAB => {
+                    self.view.active(COMPONENT_TE_INPUT_TRACK);
+                    None
+                }
+                (COMPONENT_TE_INPUT_TRACK, key) if key == &MSG_KEY_TAB => {
+                    self.view.active(COMPONENT_TE_INPUT_DISC);
+                    None
+                }
+                (COMPONENT_TE_INPUT_DISC, key) if key == &MSG_KEY_TAB => {
+                    self.view.active(COMPONENT_TE_INPUT_COMMENT);
+                    None
+                }
+                (COMPONENT_TE_INPUT_COMMENT, key) if key == &MSG_KEY_TAB => {
                     self.view.active(COMPONENT_TE_SCROLLTABLE_OPTIONS);
                     None
                 }
@@ -87,10 +118,46 @@ impl TagEditorActivity {
                 }
 
                 (COMPONENT_TE_DELETE_LYRIC, key) if key == &MSG_KEY_TAB => {
+                    self.view.active(COMPONENT_TE_TABLE_PICTURES);
+                    None
+                }
+
+                (COMPONENT_TE_TABLE_PICTURES, key) if key == &MSG_KEY_TAB => {
+                    self.view.active(COMPONENT_TE_INPUT_COVER);
+                    None
+                }
+
+                (COMPONENT_TE_INPUT_COVER, key) if key == &MSG_KEY_TAB => {
                     self.view.active(COMPONENT_TE_TEXTAREA_LYRIC);
                     None
                 }
 
+                // delete picture
+                (COMPONENT_TE_TABLE_PICTURES, key) if key == &MSG_KEY_CHAR_D => {
+                    if let Some(Payload::One(Value::Usize(index))) =
+                        self.view.get_state(COMPONENT_TE_TABLE_PICTURES)
+                    {
+                        if let Some(mut song) = self.song.clone() {
+                            song.remove_picture(index);
+                            self.save_song(&mut song);
+                        }
+                    }
+                    None
+                }
+
+                // change picture type
+                (COMPONENT_TE_TABLE_PICTURES, key) if key == &MSG_KEY_CHAR_T => {
+                    if let Some(Payload::One(Value::Usize(index))) =
+                        self.view.get_state(COMPONENT_TE_TABLE_PICTURES)
+                    {
+                        if let Some(mut song) = self.song.clone() {
+                            song.next_picture_type(index);
+                            self.save_song(&mut song);
+                        }
+                    }
+                    None
+                }
+
                 (COMPONENT_TE_TEXTAREA_LYRIC, key) if key == &MSG_KEY_TAB => {
                     self.view.active(COMPONENT_TE_INPUT_ARTIST);
                     None
@@ -98,18 +165,12 @@ impl TagEditorActivity {
                 (COMPONENT_TE_RADIO_TAG, Msg::OnSubmit(Payload::One(Value::Usize(choice)))) => {
                     if *choice == 0 {
                         // Rename file by Tag
+                        self.save_tag_by_input();
+                    }
+                    if *choice == 1 {
+                        // Rename file by the format of its content
                         if let Some(mut song) = self.song.clone() {
-                            if let Some(Payload::One(Value::Str(artist))) =
-                                self.view.get_state(COMPONENT_TE_INPUT_ARTIST)
-                            {
-                                song.set_artist(&artist);
-                            }
-                            if let Some(Payload::One(Value::Str(title))) =
-                                self.view.get_state(COMPONENT_TE_INPUT_SONGNAME)
-                            {
-                                song.set_title(&title);
-                            }
-                            match song.save_tag() {
+                            match song.fix_extension() {
                                 Ok(()) => {
                                     if let Some(file) = song.file() {
                                         self.exit_reason =
@@ -118,7 +179,7 @@ impl TagEditorActivity {
                                     self.init_by_song(&song);
                                 }
                                 Err(e) => self.mount_error(&e.to_string()),
-                            };
+                            }
                         }
                     }
                     None
@@ -218,6 +279,25 @@ impl TagEditorActivity {
                     None
                 }
 
+                (
+                    COMPONENT_TE_INPUT_ALBUM_ARTIST
+                    | COMPONENT_TE_INPUT_COMPOSER
+                    | COMPONENT_TE_INPUT_GENRE
+                    | COMPONENT_TE_INPUT_YEAR
+                    | COMPONENT_TE_INPUT_TRACK
+                    | COMPONENT_TE_INPUT_DISC
+                    | COMPONENT_TE_INPUT_COMMENT,
+                    Msg::OnSubmit(Payload::One(Value::Str(_))),
+                ) => {
+                    self.save_tag_by_input();
+                    None
+                }
+
+                (COMPONENT_TE_INPUT_COVER, Msg::OnSubmit(Payload::One(Value::Str(_)))) => {
+                    self.embed_cover();
+                    None
+                }
+
                 (
                     COMPONENT_TE_INPUT_ARTIST | COMPONENT_TE_INPUT_SONGNAME,
                     Msg::OnSubmit(Payload::One(Value::Str(_))),
@@ -354,6 +434,83 @@ impl TagEditorActivity {
         }
     }
 
+    // save_tag_by_input saves artist, title and the extended fields as typed in the inputs
+    fn save_tag_by_input(&mut self) {
+        if let Some(mut song) = self.song.clone() {
+            if let Some(artist) = self.input_value(COMPONENT_TE_INPUT_ARTIST) {
+                song.set_artist(&artist);
+            }
+            if let Some(title) = self.input_value(COMPONENT_TE_INPUT_SONGNAME) {
+                song.set_title(&title);
+            }
+            let mut fields = song.fields().clone();
+            fields.album_artist = self.input_value(COMPONENT_TE_INPUT_ALBUM_ARTIST);
+            fields.composer = self.input_value(COMPONENT_TE_INPUT_COMPOSER);
+            fields.genre = self.input_value(COMPONENT_TE_INPUT_GENRE);
+            fields.year = self.input_value(COMPONENT_TE_INPUT_YEAR);
+            fields.comment = self.input_value(COMPONENT_TE_INPUT_COMMENT);
+            fields.set_track_pair(
+                &self
+                    .input_value(COMPONENT_TE_INPUT_TRACK)
+                    .unwrap_or_default(),
+            );
+            fields.set_disc_pair(
+                &self
+                    .input_value(COMPONENT_TE_INPUT_DISC)
+                    .unwrap_or_default(),
+            );
+            song.set_fields(fields);
+            self.save_song(&mut song);
+        }
+    }
+
+    // embed_cover embeds the image typed in cover input, or the image of the album folder
+    fn embed_cover(&mut self) {
+        let Some(mut song) = self.song.clone() else {
+            return;
+        };
+        let path = self.input_value(COMPONENT_TE_INPUT_COVER).map_or_else(
+            || {
+                song.file()
+                    .and_then(|f| Path::new(f).parent())
+                    .and_then(find_folder_cover)
+            },
+            |p| Some(PathBuf::from(shellexpand::tilde(&p).to_string())),
+        );
+        let Some(path) = path else {
+            self.mount_error("no cover image found in folder");
+            return;
+        };
+        match load_cover(&path, self.cover_max_size, self.cover_quality) {
+            Ok(picture) => {
+                song.set_photo(picture);
+                self.save_song(&mut song);
+            }
+            Err(e) => self.mount_error(&format!("load cover error: {}", e)),
+        }
+    }
+
+    // save_song writes the tags and reloads the editor with the saved song
+    fn save_song(&mut self, song: &mut Song) {
+        match song.save_tag() {
+            Ok(()) => {
+                if let Some(file) = song.file() {
+                    self.exit_reason = Some(ExitReason::NeedRefreshPlaylist(file.to_string()));
+                }
+                self.init_by_song(song);
+            }
+            Err(e) => self.mount_error(&e.to_string()),
+        }
+    }
+
+    // input_value returns the trimmed text of an input, empty input is None
+    fn input_value(&self, component: &str) -> Option<String> {
+        match self.view.get_state(component) {
+            Some(Payload::One(Value::Str(s))) if !s.trim().is_empty() => Some(s.trim().to_string()),
+            _ => None,
+        }
+    }
+
     pub fn update_download_progress(&mut self) {
         if let Ok(transfer_state) = self.receiver.try_recv() {
             match transfer_state {
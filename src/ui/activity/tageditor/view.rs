@@ -23,16 +23,20 @@
  */
 // Locals
 use super::{
-    TagEditorActivity, COMPONENT_TE_DELETE_LYRIC, COMPONENT_TE_INPUT_ARTIST,
-    COMPONENT_TE_INPUT_SONGNAME, COMPONENT_TE_LABEL_HELP, COMPONENT_TE_LABEL_HINT,
-    COMPONENT_TE_RADIO_TAG, COMPONENT_TE_SCROLLTABLE_OPTIONS, COMPONENT_TE_SELECT_LYRIC,
+    TagEditorActivity, COMPONENT_TE_DELETE_LYRIC, COMPONENT_TE_INPUT_ALBUM_ARTIST,
+    COMPONENT_TE_INPUT_ARTIST, COMPONENT_TE_INPUT_COMMENT, COMPONENT_TE_INPUT_COMPOSER,
+    COMPONENT_TE_INPUT_COVER, COMPONENT_TE_INPUT_DISC, COMPONENT_TE_INPUT_GENRE,
+    COMPONENT_TE_INPUT_SONGNAME, COMPONENT_TE_INPUT_TRACK, COMPONENT_TE_INPUT_YEAR,
+    COMPONENT_TE_LABEL_HELP, COMPONENT_TE_LABEL_HINT, COMPONENT_TE_RADIO_TAG,
+    COMPONENT_TE_SCROLLTABLE_OPTIONS, COMPONENT_TE_SELECT_LYRIC, COMPONENT_TE_TABLE_PICTURES,
     COMPONENT_TE_TEXTAREA_LYRIC, COMPONENT_TE_TEXT_ERROR, COMPONENT_TE_TEXT_HELP,
 };
 use crate::{
-    song::Song,
+    song::{find_folder_cover, Song},
     ui::{components::counter, draw_area_in},
 };
 // Ext
+use std::path::Path;
 use tui_realm_stdlib::{
     Input, InputPropsBuilder, Label, LabelPropsBuilder, Paragraph, ParagraphPropsBuilder, Radio,
     RadioPropsBuilder, Select, SelectPropsBuilder, Table, TablePropsBuilder, Textarea,
@@ -44,11 +48,11 @@ use tuirealm::{
         TableBuilder, TextSpan,
     },
     tui::{
-        layout::{Alignment, Constraint, Direction, Layout},
+        layout::{Alignment, Constraint, Direction, Layout, Rect},
         style::Color,
         widgets::Clear,
     },
-    PropsBuilder, View,
+    Frame, PropsBuilder, View,
 };
 
 // tui
@@ -97,7 +101,7 @@ impl TagEditorActivity {
                     .with_inverted_color(Color::Black)
                     .with_value(0)
                     .with_title("Additional operation:", Alignment::Left)
-                    .with_options(&["Rename file by Tag"])
+                    .with_options(&["Rename file by Tag", "Fix extension"])
                     .build(),
             )),
         );
@@ -122,6 +126,27 @@ impl TagEditorActivity {
                     .build(),
             )),
         );
+        // extended fields, saved on <ENTER>
+        for (component, label) in &[
+            (COMPONENT_TE_INPUT_ALBUM_ARTIST, "Album Artist"),
+            (COMPONENT_TE_INPUT_COMPOSER, "Composer"),
+            (COMPONENT_TE_INPUT_GENRE, "Genre"),
+            (COMPONENT_TE_INPUT_YEAR, "Year"),
+            (COMPONENT_TE_INPUT_TRACK, "Track"),
+            (COMPONENT_TE_INPUT_DISC, "Disc"),
+            (COMPONENT_TE_INPUT_COMMENT, "Comment"),
+        ] {
+            self.view.mount(
+                component,
+                Box::new(Input::new(
+                    InputPropsBuilder::default()
+                        .with_borders(Borders::ALL, BorderType::Rounded, Color::LightYellow)
+                        .with_foreground(Color::Cyan)
+                        .with_label(String::from(*label), Alignment::Left)
+                        .build(),
+                )),
+            );
+        }
         // Scrolltable
         self.view.mount(
             COMPONENT_TE_SCROLLTABLE_OPTIONS,
@@ -173,6 +198,44 @@ impl TagEditorActivity {
             )),
         );
 
+        // Pictures
+        self.view.mount(
+            COMPONENT_TE_TABLE_PICTURES,
+            Box::new(Table::new(
+                TablePropsBuilder::default()
+                    .with_background(Color::Black)
+                    .with_highlighted_str(Some("\u{1f680}"))
+                    .with_highlighted_color(Color::LightBlue)
+                    .with_max_scroll_step(4)
+                    .with_borders(Borders::ALL, BorderType::Rounded, Color::LightRed)
+                    .with_title("Pictures", Alignment::Left)
+                    .scrollable(true)
+                    .with_header(&["Type", "Format", "Size"])
+                    .with_widths(&[50, 25, 25])
+                    .with_table(
+                        TableBuilder::default()
+                            .add_col(TextSpan::from("No Pictures."))
+                            .build(),
+                    )
+                    .build(),
+            )),
+        );
+
+        // Cover file
+        self.view.mount(
+            COMPONENT_TE_INPUT_COVER,
+            Box::new(Input::new(
+                InputPropsBuilder::default()
+                    .with_borders(Borders::ALL, BorderType::Rounded, Color::LightRed)
+                    .with_foreground(Color::Cyan)
+                    .with_label(
+                        String::from("Cover image, empty for folder image"),
+                        Alignment::Left,
+                    )
+                    .build(),
+            )),
+        );
+
         // Lyric Textarea
         self.view.mount(
             COMPONENT_TE_TEXTAREA_LYRIC,
@@ -207,6 +270,7 @@ impl TagEditorActivity {
                         [
                             Constraint::Length(1),
                             Constraint::Length(3),
+                            Constraint::Length(3),
                             Constraint::Min(2),
                             Constraint::Length(1),
                         ]
@@ -230,12 +294,20 @@ impl TagEditorActivity {
                     .direction(Direction::Horizontal)
                     .margin(0)
                     .constraints([Constraint::Ratio(3, 5), Constraint::Ratio(2, 5)].as_ref())
-                    .split(chunks_main[2]);
+                    .split(chunks_main[3]);
 
                 let chunks_middle2_right = Layout::default()
                     .direction(Direction::Vertical)
                     .margin(0)
-                    .constraints([Constraint::Length(6), Constraint::Min(2)].as_ref())
+                    .constraints(
+                        [
+                            Constraint::Length(6),
+                            Constraint::Length(7),
+                            Constraint::Length(3),
+                            Constraint::Min(2),
+                        ]
+                        .as_ref(),
+                    )
                     .split(chunks_middle2[1]);
 
                 let chunks_middle2_right_top = Layout::default()
@@ -251,9 +323,10 @@ impl TagEditorActivity {
                     .render(COMPONENT_TE_INPUT_SONGNAME, f, chunks_middle1[1]);
                 self.view
                     .render(COMPONENT_TE_RADIO_TAG, f, chunks_middle1[2]);
+                self.render_fields(f, chunks_main[2]);
                 self.view
                     .render(COMPONENT_TE_SCROLLTABLE_OPTIONS, f, chunks_middle2[0]);
-                self.view.render(COMPONENT_TE_LABEL_HELP, f, chunks_main[3]);
+                self.view.render(COMPONENT_TE_LABEL_HELP, f, chunks_main[4]);
 
                 self.view
                     .render(COMPONENT_TE_SELECT_LYRIC, f, chunks_middle2_right_top[0]);
@@ -261,7 +334,11 @@ impl TagEditorActivity {
                     .render(COMPONENT_TE_DELETE_LYRIC, f, chunks_middle2_right_top[1]);
 
                 self.view
-                    .render(COMPONENT_TE_TEXTAREA_LYRIC, f, chunks_middle2_right[1]);
+                    .render(COMPONENT_TE_TABLE_PICTURES, f, chunks_middle2_right[1]);
+                self.view
+                    .render(COMPONENT_TE_INPUT_COVER, f, chunks_middle2_right[2]);
+                self.view
+                    .render(COMPONENT_TE_TEXTAREA_LYRIC, f, chunks_middle2_right[3]);
 
                 if let Some(props) = self.view.get_props(COMPONENT_TE_TEXT_ERROR) {
                     if props.visible {
@@ -285,6 +362,40 @@ impl TagEditorActivity {
         }
     }
 
+    // render_fields lays out the inputs of extended fields in one row
+    fn render_fields(&self, f: &mut Frame, area: Rect) {
+        let chunks_fields = Layout::default()
+            .direction(Direction::Horizontal)
+            .margin(0)
+            .constraints(
+                [
+                    Constraint::Ratio(2, 12),
+                    Constraint::Ratio(2, 12),
+                    Constraint::Ratio(2, 12),
+                    Constraint::Ratio(1, 12),
+                    Constraint::Ratio(1, 12),
+                    Constraint::Ratio(1, 12),
+                    Constraint::Ratio(3, 12),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+        for (i, component) in [
+            COMPONENT_TE_INPUT_ALBUM_ARTIST,
+            COMPONENT_TE_INPUT_COMPOSER,
+            COMPONENT_TE_INPUT_GENRE,
+            COMPONENT_TE_INPUT_YEAR,
+            COMPONENT_TE_INPUT_TRACK,
+            COMPONENT_TE_INPUT_DISC,
+            COMPONENT_TE_INPUT_COMMENT,
+        ]
+        .iter()
+        .enumerate()
+        {
+            self.view.render(component, f, chunks_fields[i]);
+        }
+    }
+
     // -- mount
 
     // ### mount_error
@@ -318,6 +429,7 @@ impl TagEditorActivity {
     // initialize the value in tageditor based on info from Song
     pub fn init_by_song(&mut self, s: &Song) {
         self.song = Some(s.clone());
+        self.update_hint(s);
         if let Some(artist) = s.artist() {
             if let Some(props) = self.view.get_props(COMPONENT_TE_INPUT_ARTIST) {
                 let props = InputPropsBuilder::from(props)
@@ -336,6 +448,10 @@ impl TagEditorActivity {
             }
         }
 
+        self.update_pictures(s);
+        self.update_cover_input(s);
+        self.update_fields(s);
+
         if s.lyric_frames.is_empty() {
             if let Some(props) = self.view.get_props(COMPONENT_TE_SELECT_LYRIC) {
                 let props = SelectPropsBuilder::from(props)
@@ -365,11 +481,11 @@ impl TagEditorActivity {
             return;
         }
 
+        // same order as lyric_frames, so the selected index points to the right lyric
         let mut vec_lang: Vec<String> = vec![];
         for l in &s.lyric_frames {
             vec_lang.push(l.description.clone());
         }
-        vec_lang.sort();
 
         if let Some(props) = self.view.get_props(COMPONENT_TE_SELECT_LYRIC) {
             let props = SelectPropsBuilder::from(props)
@@ -407,6 +523,87 @@ impl TagEditorActivity {
         }
     }
 
+    // update_cover_input suggests the image found in the album folder
+    fn update_cover_input(&mut self, s: &Song) {
+        let folder_cover = s
+            .file()
+            .and_then(|f| Path::new(f).parent())
+            .and_then(find_folder_cover);
+        if let Some(props) = self.view.get_props(COMPONENT_TE_INPUT_COVER) {
+            let props = InputPropsBuilder::from(props)
+                .with_value(
+                    folder_cover.map_or_else(String::new, |p| p.to_string_lossy().to_string()),
+                )
+                .build();
+            self.view.update(COMPONENT_TE_INPUT_COVER, props);
+        }
+    }
+
+    fn update_fields(&mut self, s: &Song) {
+        let fields = s.fields();
+        for (component, value) in [
+            (COMPONENT_TE_INPUT_ALBUM_ARTIST, fields.album_artist.clone()),
+            (COMPONENT_TE_INPUT_COMPOSER, fields.composer.clone()),
+            (COMPONENT_TE_INPUT_GENRE, fields.genre.clone()),
+            (COMPONENT_TE_INPUT_YEAR, fields.year.clone()),
+            (COMPONENT_TE_INPUT_TRACK, Some(fields.track_pair())),
+            (COMPONENT_TE_INPUT_DISC, Some(fields.disc_pair())),
+            (COMPONENT_TE_INPUT_COMMENT, fields.comment.clone()),
+        ] {
+            if let Some(props) = self.view.get_props(component) {
+                let props = InputPropsBuilder::from(props)
+                    .with_value(value.unwrap_or_default())
+                    .build();
+                self.view.update(component, props);
+            }
+        }
+    }
+
+    fn update_pictures(&mut self, s: &Song) {
+        let mut table = TableBuilder::default();
+        for (idx, p) in s.pictures.iter().enumerate() {
+            if idx > 0 {
+                table.add_row();
+            }
+            table
+                .add_col(TextSpan::new(p.picture_type.to_string()).fg(Color::LightYellow))
+                .add_col(TextSpan::from(p.mime_type.as_str()))
+                .add_col(TextSpan::from(format!("{} KB", p.data.len() / 1024)));
+        }
+        if s.pictures.is_empty() {
+            table.add_col(TextSpan::from("No Pictures."));
+        }
+        if let Some(props) = self.view.get_props(COMPONENT_TE_TABLE_PICTURES) {
+            let props = TablePropsBuilder::from(props)
+                .with_table(table.build())
+                .build();
+            self.view.update(COMPONENT_TE_TABLE_PICTURES, props);
+        }
+    }
+
+    // update_hint warns when the extension doesn't match the content of the file
+    fn update_hint(&mut self, s: &Song) {
+        let (text, color) = s.format_mismatch().map_or_else(
+            || (String::from("Press <ENTER> to search:"), Color::Magenta),
+            |format| {
+                (
+                    format!(
+                        "Warning: the file is {} but the extension is not. Choose \"Fix extension\" to rename it.",
+                        format
+                    ),
+                    Color::Red,
+                )
+            },
+        );
+        if let Some(props) = self.view.get_props(COMPONENT_TE_LABEL_HINT) {
+            let props = LabelPropsBuilder::from(props)
+                .with_foreground(color)
+                .with_text(text)
+                .build();
+            self.view.update(COMPONENT_TE_LABEL_HINT, props);
+        }
+    }
+
     pub(super) fn mount_help(&mut self) {
         self.view.mount(
             COMPONENT_TE_TEXT_HELP,
@@ -421,6 +618,11 @@ impl TagEditorActivity {
                             .add_col(TextSpan::new("<ENTER>").bold().fg(Color::Cyan))
                             .add_col(TextSpan::from("Search when focus Artist or Song name."))
                             .add_row()
+                            .add_col(TextSpan::new("<ENTER>").bold().fg(Color::Cyan))
+                            .add_col(TextSpan::from(
+                                "Save tags when focus Album Artist, Composer, Genre, Year, Track, Disc or Comment.",
+                            ))
+                            .add_row()
                             .add_col(TextSpan::new("<ESC> or <Q>").bold().fg(Color::Cyan))
                             .add_col(TextSpan::from("Exit"))
                             .add_row()
@@ -435,6 +637,17 @@ impl TagEditorActivity {
                             .add_row()
                             .add_col(TextSpan::new("<s>").bold().fg(Color::Cyan))
                             .add_col(TextSpan::from("Download selected song"))
+                            .add_row()
+                            .add_col(TextSpan::new("<d>").bold().fg(Color::Cyan))
+                            .add_col(TextSpan::from("Delete selected picture"))
+                            .add_row()
+                            .add_col(TextSpan::new("<t>").bold().fg(Color::Cyan))
+                            .add_col(TextSpan::from("Change type of selected picture"))
+                            .add_row()
+                            .add_col(TextSpan::new("<ENTER>").bold().fg(Color::Cyan))
+                            .add_col(TextSpan::from(
+                                "Embed cover image when focus Cover, empty for folder image.",
+                            ))
                             .build(),
                     )
                     .build(),
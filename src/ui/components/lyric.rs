@@ -0,0 +1,248 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+// lyric paragraph is like the paragraph of stdlib, but each line is made of several spans, so the
+// sung words of karaoke lyrics can be highlighted inside the line. It scrolls to keep the current
+// line in the middle.
+use tuirealm::{
+    event::Event,
+    props::{
+        Alignment, BlockTitle, BordersProps, PropPayload, PropValue, Props, PropsBuilder, Table,
+        TextSpan,
+    },
+    tui::{
+        layout::Rect,
+        style::{Color, Style},
+        text::{Span, Spans},
+        widgets::{BorderType, Borders, Paragraph, Wrap},
+    },
+    Component, Frame, Msg, Payload,
+};
+
+// -- Props
+
+const PROP_LINES: &str = "lines";
+const PROP_CURRENT: &str = "current";
+const PROP_ALIGNMENT: &str = "alignment";
+
+#[allow(clippy::module_name_repetitions)]
+pub struct LyricPropsBuilder {
+    props: Option<Props>,
+}
+
+impl Default for LyricPropsBuilder {
+    fn default() -> Self {
+        Self {
+            props: Some(Props::default()),
+        }
+    }
+}
+
+impl PropsBuilder for LyricPropsBuilder {
+    fn build(&mut self) -> Props {
+        self.props.take().unwrap_or_default()
+    }
+
+    fn hidden(&mut self) -> &mut Self {
+        if let Some(props) = self.props.as_mut() {
+            props.visible = false;
+        }
+        self
+    }
+
+    fn visible(&mut self) -> &mut Self {
+        if let Some(props) = self.props.as_mut() {
+            props.visible = true;
+        }
+        self
+    }
+}
+
+impl From<Props> for LyricPropsBuilder {
+    fn from(props: Props) -> Self {
+        Self { props: Some(props) }
+    }
+}
+
+impl LyricPropsBuilder {
+    pub const fn with_foreground(&mut self, color: Color) -> &mut Self {
+        if let Some(props) = self.props.as_mut() {
+            props.foreground = color;
+        }
+        self
+    }
+
+    pub const fn with_borders(
+        &mut self,
+        borders: Borders,
+        variant: BorderType,
+        color: Color,
+    ) -> &mut Self {
+        if let Some(props) = self.props.as_mut() {
+            props.borders = BordersProps {
+                borders,
+                variant,
+                color,
+            }
+        }
+        self
+    }
+
+    pub fn with_title<S: AsRef<str>>(&mut self, title: S, alignment: Alignment) -> &mut Self {
+        if let Some(props) = self.props.as_mut() {
+            props.title = Some(BlockTitle::new(title, alignment));
+        }
+        self
+    }
+
+    // with_lines sets the lines, each line is a row of spans
+    pub fn with_lines(&mut self, lines: Table) -> &mut Self {
+        if let Some(props) = self.props.as_mut() {
+            props
+                .own
+                .insert(PROP_LINES, PropPayload::One(PropValue::Table(lines)));
+        }
+        self
+    }
+
+    // with_text sets a single line with one span
+    pub fn with_text(&mut self, span: TextSpan) -> &mut Self {
+        self.with_current(0).with_lines(vec![vec![span]])
+    }
+
+    // with_current sets the index of the line being sung
+    pub fn with_current(&mut self, index: usize) -> &mut Self {
+        if let Some(props) = self.props.as_mut() {
+            props
+                .own
+                .insert(PROP_CURRENT, PropPayload::One(PropValue::Usize(index)));
+        }
+        self
+    }
+
+    pub fn with_alignment(&mut self, alignment: Alignment) -> &mut Self {
+        if let Some(props) = self.props.as_mut() {
+            props.own.insert(
+                PROP_ALIGNMENT,
+                PropPayload::One(PropValue::Alignment(alignment)),
+            );
+        }
+        self
+    }
+}
+
+// -- Component
+
+pub struct LyricParagraph {
+    props: Props,
+}
+
+impl LyricParagraph {
+    pub const fn new(props: Props) -> Self {
+        Self { props }
+    }
+}
+
+impl Component for LyricParagraph {
+    fn render(&self, render: &mut Frame, area: Rect) {
+        if self.props.visible {
+            let block = tui_realm_stdlib::utils::get_block(
+                &self.props.borders,
+                self.props.title.as_ref(),
+                true,
+            );
+            // lines before the current one are scrolled out, up to half of the height
+            let current = match self.props.own.get(PROP_CURRENT) {
+                Some(PropPayload::One(PropValue::Usize(index))) => *index,
+                _ => 0,
+            };
+            let first = current.saturating_sub(usize::from(block.inner(area).height / 2));
+            let text: Vec<Spans> = match self.props.own.get(PROP_LINES) {
+                Some(PropPayload::One(PropValue::Table(lines))) => lines
+                    .iter()
+                    .skip(first)
+                    .map(|line| {
+                        Spans::from(
+                            line.iter()
+                                .map(|x| {
+                                    let (fg, bg, modifiers) =
+                                        tui_realm_stdlib::utils::use_or_default_styles(
+                                            &self.props,
+                                            x,
+                                        );
+                                    Span::styled(
+                                        x.content.clone(),
+                                        Style::default().add_modifier(modifiers).fg(fg).bg(bg),
+                                    )
+                                })
+                                .collect::<Vec<Span>>(),
+                        )
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            };
+            let alignment = match self.props.own.get(PROP_ALIGNMENT) {
+                Some(PropPayload::One(PropValue::Alignment(alignment))) => *alignment,
+                _ => Alignment::Left,
+            };
+            render.render_widget(
+                Paragraph::new(text)
+                    .block(block)
+                    .style(
+                        Style::default()
+                            .fg(self.props.foreground)
+                            .bg(self.props.background),
+                    )
+                    .alignment(alignment)
+                    .wrap(Wrap { trim: false }),
+                area,
+            );
+        }
+    }
+
+    fn update(&mut self, props: Props) -> Msg {
+        self.props = props;
+        Msg::None
+    }
+
+    fn get_props(&self) -> Props {
+        self.props.clone()
+    }
+
+    fn on(&mut self, ev: Event) -> Msg {
+        // Return key event to activity
+        if let Event::Key(key) = ev {
+            Msg::OnKey(key)
+        } else {
+            Msg::None
+        }
+    }
+
+    fn get_state(&self) -> Payload {
+        Payload::None
+    }
+
+    fn blur(&mut self) {}
+
+    fn active(&mut self) {}
+}
@@ -22,3 +22,4 @@
  * SOFTWARE.
  */
 pub mod counter;
+pub mod lyric;
@@ -79,10 +79,15 @@ pub const MSG_KEY_SPACE: Msg = Msg::OnKey(KeyEvent {
 
 // // -- char keys
 
-// pub const MSG_KEY_CHAR_A: Msg = Msg::OnKey(KeyEvent {
-//     code: KeyCode::Char('a'),
-//     modifiers: KeyModifiers::NONE,
-// });
+pub const MSG_KEY_CHAR_A: Msg = Msg::OnKey(KeyEvent {
+    code: KeyCode::Char('a'),
+    modifiers: KeyModifiers::NONE,
+});
+
+pub const MSG_KEY_CHAR_CAPITAL_A: Msg = Msg::OnKey(KeyEvent {
+    code: KeyCode::Char('A'),
+    modifiers: KeyModifiers::SHIFT,
+});
 pub const MSG_KEY_CHAR_B: Msg = Msg::OnKey(KeyEvent {
     code: KeyCode::Char('b'),
     modifiers: KeyModifiers::NONE,
@@ -101,6 +106,10 @@ pub const MSG_KEY_CHAR_CAPITAL_B: Msg = Msg::OnKey(KeyEvent {
 //     code: KeyCode::Char('c'),
 //     modifiers: KeyModifiers::NONE,
 // });
+pub const MSG_KEY_CHAR_CAPITAL_C: Msg = Msg::OnKey(KeyEvent {
+    code: KeyCode::Char('C'),
+    modifiers: KeyModifiers::SHIFT,
+});
 pub const MSG_KEY_CHAR_D: Msg = Msg::OnKey(KeyEvent {
     code: KeyCode::Char('d'),
     modifiers: KeyModifiers::NONE,
@@ -111,10 +120,10 @@ pub const MSG_KEY_CHAR_CAPITAL_D: Msg = Msg::OnKey(KeyEvent {
     modifiers: KeyModifiers::SHIFT,
 });
 
-// pub const MSG_KEY_CHAR_E: Msg = Msg::OnKey(KeyEvent {
-//     code: KeyCode::Char('e'),
-//     modifiers: KeyModifiers::NONE,
-// });
+pub const MSG_KEY_CHAR_E: Msg = Msg::OnKey(KeyEvent {
+    code: KeyCode::Char('e'),
+    modifiers: KeyModifiers::NONE,
+});
 pub const MSG_KEY_CHAR_F: Msg = Msg::OnKey(KeyEvent {
     code: KeyCode::Char('f'),
     modifiers: KeyModifiers::NONE,
@@ -155,6 +164,10 @@ pub const MSG_KEY_CHAR_K: Msg = Msg::OnKey(KeyEvent {
     code: KeyCode::Char('k'),
     modifiers: KeyModifiers::NONE,
 });
+pub const MSG_KEY_CHAR_CAPITAL_K: Msg = Msg::OnKey(KeyEvent {
+    code: KeyCode::Char('K'),
+    modifiers: KeyModifiers::SHIFT,
+});
 // */
 pub const MSG_KEY_CHAR_L: Msg = Msg::OnKey(KeyEvent {
     code: KeyCode::Char('l'),
@@ -165,12 +178,14 @@ pub const MSG_KEY_CHAR_CAPITAL_L: Msg = Msg::OnKey(KeyEvent {
     modifiers: KeyModifiers::SHIFT,
 });
 
-// /*
-// pub const MSG_KEY_CHAR_M: Msg = Msg::OnKey(KeyEvent {
-//     code: KeyCode::Char('m'),
-//     modifiers: KeyModifiers::NONE,
-// });
-// */
+pub const MSG_KEY_CHAR_M: Msg = Msg::OnKey(KeyEvent {
+    code: KeyCode::Char('m'),
+    modifiers: KeyModifiers::NONE,
+});
+pub const MSG_KEY_CHAR_CAPITAL_M: Msg = Msg::OnKey(KeyEvent {
+    code: KeyCode::Char('M'),
+    modifiers: KeyModifiers::SHIFT,
+});
 pub const MSG_KEY_CHAR_N: Msg = Msg::OnKey(KeyEvent {
     code: KeyCode::Char('n'),
     modifiers: KeyModifiers::NONE,
@@ -180,10 +195,10 @@ pub const MSG_KEY_CHAR_CAPITAL_N: Msg = Msg::OnKey(KeyEvent {
     modifiers: KeyModifiers::SHIFT,
 });
 
-// pub const MSG_KEY_CHAR_O: Msg = Msg::OnKey(KeyEvent {
-//     code: KeyCode::Char('o'),
-//     modifiers: KeyModifiers::NONE,
-// });
+pub const MSG_KEY_CHAR_O: Msg = Msg::OnKey(KeyEvent {
+    code: KeyCode::Char('o'),
+    modifiers: KeyModifiers::NONE,
+});
 // /*
 pub const MSG_KEY_CHAR_P: Msg = Msg::OnKey(KeyEvent {
     code: KeyCode::Char('p'),
@@ -214,6 +229,14 @@ pub const MSG_KEY_CHAR_DASH: Msg = Msg::OnKey(KeyEvent {
     code: KeyCode::Char('_'),
     modifiers: KeyModifiers::NONE,
 });
+pub const MSG_KEY_CHAR_LEFT_BRACKET: Msg = Msg::OnKey(KeyEvent {
+    code: KeyCode::Char('['),
+    modifiers: KeyModifiers::NONE,
+});
+pub const MSG_KEY_CHAR_RIGHT_BRACKET: Msg = Msg::OnKey(KeyEvent {
+    code: KeyCode::Char(']'),
+    modifiers: KeyModifiers::NONE,
+});
 
 pub const MSG_KEY_CHAR_R: Msg = Msg::OnKey(KeyEvent {
     code: KeyCode::Char('r'),
@@ -235,29 +258,31 @@ pub const MSG_KEY_CHAR_T: Msg = Msg::OnKey(KeyEvent {
 //     modifiers: KeyModifiers::NONE,
 // });
 // /*
-// pub const MSG_KEY_CHAR_V: Msg = Msg::OnKey(KeyEvent {
-//     code: KeyCode::Char('v'),
-//     modifiers: KeyModifiers::NONE,
-// });
-// pub const MSG_KEY_CHAR_W: Msg = Msg::OnKey(KeyEvent {
-//     code: KeyCode::Char('w'),
-//     modifiers: KeyModifiers::NONE,
-// });
+pub const MSG_KEY_CHAR_V: Msg = Msg::OnKey(KeyEvent {
+    code: KeyCode::Char('v'),
+    modifiers: KeyModifiers::NONE,
+});
 // */
-// pub const MSG_KEY_CHAR_X: Msg = Msg::OnKey(KeyEvent {
-//     code: KeyCode::Char('x'),
-//     modifiers: KeyModifiers::NONE,
-// });
+pub const MSG_KEY_CHAR_W: Msg = Msg::OnKey(KeyEvent {
+    code: KeyCode::Char('w'),
+    modifiers: KeyModifiers::NONE,
+});
+pub const MSG_KEY_CHAR_X: Msg = Msg::OnKey(KeyEvent {
+    code: KeyCode::Char('x'),
+    modifiers: KeyModifiers::NONE,
+});
+pub const MSG_KEY_CHAR_CAPITAL_X: Msg = Msg::OnKey(KeyEvent {
+    code: KeyCode::Char('X'),
+    modifiers: KeyModifiers::SHIFT,
+});
 pub const MSG_KEY_CHAR_Y: Msg = Msg::OnKey(KeyEvent {
     code: KeyCode::Char('y'),
     modifiers: KeyModifiers::NONE,
 });
-// /*
-// pub const MSG_KEY_CHAR_Z: Msg = Msg::OnKey(KeyEvent {
-//     code: KeyCode::Char('z'),
-//     modifiers: KeyModifiers::NONE,
-// });
-// */
+pub const MSG_KEY_CHAR_Z: Msg = Msg::OnKey(KeyEvent {
+    code: KeyCode::Char('z'),
+    modifiers: KeyModifiers::NONE,
+});
 // // -- control
 // pub const MSG_KEY_CTRL_C: Msg = Msg::OnKey(KeyEvent {
 //     code: KeyCode::Char('c'),
@@ -272,6 +297,10 @@ pub const MSG_KEY_CTRL_H: Msg = Msg::OnKey(KeyEvent {
     code: KeyCode::Char('h'),
     modifiers: KeyModifiers::CONTROL,
 });
+pub const MSG_KEY_CTRL_T: Msg = Msg::OnKey(KeyEvent {
+    code: KeyCode::Char('t'),
+    modifiers: KeyModifiers::CONTROL,
+});
 
 // pub const MSG_KEY_QUESTION_MARK: Msg = Msg::OnKey(KeyEvent {
 //     code: KeyCode::Char('?'),